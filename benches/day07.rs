@@ -0,0 +1,34 @@
+use advent_of_code_2023::day07::Rules;
+use advent_of_code_2023::lcg::Lcg;
+use criterion::{criterion_group, criterion_main, Criterion};
+
+const HANDS: usize = 1_000_000;
+
+// deterministic (hand, bid) pairs drawn with a small lcg
+fn generate_hands(count: usize) -> Vec<(String, u64)> {
+    let cards = b"23456789TJQKA";
+    let mut lcg = Lcg::new(2023);
+    (0..count)
+        .map(|_| {
+            let hand = (0..5)
+                .map(|_| cards[lcg.below(cards.len())] as char)
+                .collect();
+            (hand, lcg.next_u64() % 1000 + 1)
+        })
+        .collect()
+}
+
+fn rank_hands(c: &mut Criterion) {
+    let owned = generate_hands(HANDS);
+    let hands: Vec<(&str, u64)> = owned.iter().map(|(h, b)| (h.as_str(), *b)).collect();
+
+    let mut group = c.benchmark_group("day07 1M hands");
+    group.sample_size(10);
+    for (name, rules) in [("part1", Rules::part1()), ("part2", Rules::part2())] {
+        group.bench_function(name, |b| b.iter(|| rules.total_winnings(&hands)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, rank_hands);
+criterion_main!(benches);
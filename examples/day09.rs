@@ -0,0 +1,33 @@
+use std::env;
+use std::fs;
+
+use advent_of_code_2023::day09::{parse_histories, Classification};
+
+// Prints the polynomial behind every history and where it goes `steps` past
+// the end (negative to go back before the start):
+//   cargo run --example day09 -- [input] [steps]
+fn main() {
+    let mut args = env::args().skip(1);
+    let path = args.next().unwrap_or("input/2023/day9.txt".to_string());
+    let steps: i64 = args
+        .next()
+        .map(|s| s.parse().expect("steps should be a whole number"))
+        .unwrap_or(1);
+    let input = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+
+    for (line, history) in parse_histories(&input) {
+        match Classification::of(&history) {
+            Classification::Polynomial(polynomial) => {
+                // same indexing as Polynomial::extrapolate
+                let n = if steps >= 0 {
+                    (history.len() - 1) as i64 + steps
+                } else {
+                    steps
+                };
+                let value = polynomial.extrapolate(history.len(), steps);
+                println!("line {}: {}, p({}) = {}", line, polynomial, n, value);
+            }
+            other => println!("line {}: {}", line, other),
+        }
+    }
+}
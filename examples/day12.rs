@@ -0,0 +1,16 @@
+use std::env;
+use std::fs;
+
+use advent_of_code_2023::day12::Nonogram;
+
+// Solves a nonogram written as "rows" and "columns" sections with one clue
+// per line, e.g. "1,2" or "1 2", and "0" or "-" for an empty line:
+//   cargo run --example day12 -- puzzle.txt
+fn main() {
+    let path = env::args()
+        .nth(1)
+        .expect("usage: cargo run --example day12 -- puzzle.txt");
+    let input = fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e));
+
+    print!("{}", Nonogram::parse(&input).solve());
+}
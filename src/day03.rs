@@ -0,0 +1,414 @@
+use std::collections::{HashMap, HashSet};
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+
+pub fn is_digit(c: u8) -> bool {
+    c.is_ascii_digit()
+}
+
+pub fn is_symbol(c: u8) -> bool {
+    c != b'.' && !is_digit(c)
+}
+
+// values are as long as the digit runs, which edits can make arbitrarily long
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Number {
+    pub value: BigUint,
+    pub row: usize,
+    // span is [start, end)
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol {
+    pub c: u8,
+    pub row: usize,
+    pub col: usize,
+}
+
+// keeps the part number sum and gear ratio total up to date across edits,
+// with the contribution of every part number / gear keyed by its position
+#[derive(Debug, Clone)]
+pub struct Schematic {
+    grid: Vec<Vec<u8>>,
+    part_values: HashMap<(usize, usize), BigUint>,
+    gear_values: HashMap<(usize, usize), BigUint>,
+    parts_total: BigUint,
+    gears_total: BigUint,
+}
+
+impl Schematic {
+    pub fn parse(input: &str) -> Schematic {
+        let grid = input
+            .lines()
+            .map(|line| line.trim().as_bytes().to_vec())
+            .collect();
+        let mut schematic = Schematic {
+            grid,
+            part_values: HashMap::new(),
+            gear_values: HashMap::new(),
+            parts_total: BigUint::zero(),
+            gears_total: BigUint::zero(),
+        };
+        for number in schematic.numbers() {
+            schematic.store_part(&number);
+        }
+        for symbol in schematic.symbols() {
+            schematic.store_gear(symbol.row, symbol.col);
+        }
+        schematic
+    }
+
+    pub fn part_sum(&self) -> &BigUint {
+        &self.parts_total
+    }
+
+    pub fn gear_ratio_total(&self) -> &BigUint {
+        &self.gears_total
+    }
+
+    pub fn set_cell(&mut self, row: usize, col: usize, c: u8) {
+        self.edit(row, col.saturating_sub(1), col + 2, |line| line[col] = c);
+    }
+
+    // shifts the rest of the row right
+    pub fn insert(&mut self, row: usize, col: usize, c: u8) {
+        self.edit(row, col.saturating_sub(1), usize::MAX, |line| {
+            line.insert(col, c)
+        });
+    }
+
+    // shifts the rest of the row left
+    pub fn delete(&mut self, row: usize, col: usize) {
+        self.edit(row, col.saturating_sub(1), usize::MAX, |line| {
+            line.remove(col);
+        });
+    }
+
+    // Everything whose contribution can change lies in rows row-1..=row+1,
+    // cols [col_lo, col_hi): drop the stored values there (and of gears next to
+    // those numbers), apply the edit, then store the values found afterwards.
+    fn edit(&mut self, row: usize, col_lo: usize, col_hi: usize, apply: impl FnOnce(&mut Vec<u8>)) {
+        let before = self.numbers_touching(row, col_lo, col_hi);
+        for number in &before {
+            if let Some(value) = self.part_values.remove(&(number.row, number.start)) {
+                self.parts_total -= value;
+            }
+        }
+        for (r, c) in self.gears_near(&before, row, col_lo, col_hi) {
+            if let Some(ratio) = self.gear_values.remove(&(r, c)) {
+                self.gears_total -= ratio;
+            }
+        }
+
+        apply(&mut self.grid[row]);
+
+        let after = self.numbers_touching(row, col_lo, col_hi);
+        for number in &after {
+            self.store_part(number);
+        }
+        for (r, c) in self.gears_near(&after, row, col_lo, col_hi) {
+            self.store_gear(r, c);
+        }
+    }
+
+    fn store_part(&mut self, number: &Number) {
+        let key = (number.row, number.start);
+        if let Some(value) = self.part_values.remove(&key) {
+            self.parts_total -= value;
+        }
+        if !self.symbols_around(number).is_empty() {
+            self.parts_total += &number.value;
+            self.part_values.insert(key, number.value.clone());
+        }
+    }
+
+    fn store_gear(&mut self, row: usize, col: usize) {
+        if let Some(ratio) = self.gear_values.remove(&(row, col)) {
+            self.gears_total -= ratio;
+        }
+        if self.grid[row][col] != b'*' {
+            return;
+        }
+        let numbers = self.numbers_touching(row, col.saturating_sub(1), col + 2);
+        if numbers.len() == 2 {
+            let ratio = &numbers[0].value * &numbers[1].value;
+            self.gears_total += &ratio;
+            self.gear_values.insert((row, col), ratio);
+        }
+    }
+
+    // the whole number a digit belongs to
+    fn number_at(&self, row: usize, col: usize) -> Option<Number> {
+        let line = &self.grid[row];
+        if !is_digit(*line.get(col)?) {
+            return None;
+        }
+        let mut start = col;
+        while start > 0 && is_digit(line[start - 1]) {
+            start -= 1;
+        }
+        let mut end = start;
+        while end < line.len() && is_digit(line[end]) {
+            end += 1;
+        }
+        Some(Number {
+            value: parse_value(&line[start..end]),
+            row,
+            start,
+            end,
+        })
+    }
+
+    // numbers with a digit in rows row-1..=row+1, cols [col_lo, col_hi)
+    fn numbers_touching(&self, row: usize, col_lo: usize, col_hi: usize) -> Vec<Number> {
+        let mut numbers = Vec::new();
+        for r in row.saturating_sub(1)..(row + 2).min(self.rows()) {
+            let mut col = col_lo;
+            while col < col_hi.min(self.grid[r].len()) {
+                match self.number_at(r, col) {
+                    Some(number) => {
+                        col = number.end;
+                        numbers.push(number);
+                    }
+                    None => col += 1,
+                }
+            }
+        }
+        numbers
+    }
+
+    // '*' cells next to any of the numbers or inside the edited window
+    fn gears_near(
+        &self,
+        numbers: &[Number],
+        row: usize,
+        col_lo: usize,
+        col_hi: usize,
+    ) -> HashSet<(usize, usize)> {
+        let mut gears: HashSet<(usize, usize)> = numbers
+            .iter()
+            .flat_map(|number| self.symbols_around(number))
+            .filter(|symbol| symbol.c == b'*')
+            .map(|symbol| (symbol.row, symbol.col))
+            .collect();
+        for r in row.saturating_sub(1)..(row + 2).min(self.rows()) {
+            for c in col_lo..col_hi.min(self.grid[r].len()) {
+                if self.grid[r][c] == b'*' {
+                    gears.insert((r, c));
+                }
+            }
+        }
+        gears
+    }
+
+    pub fn rows(&self) -> usize {
+        self.grid.len()
+    }
+
+    pub fn cell(&self, row: usize, col: usize) -> Option<u8> {
+        self.grid.get(row).and_then(|r| r.get(col)).copied()
+    }
+
+    // every number in a single row, left to right
+    pub fn row_numbers(&self, row: usize) -> Vec<Number> {
+        let mut numbers = Vec::new();
+        let line = &self.grid[row];
+        let mut col = 0;
+        while col < line.len() {
+            if !is_digit(line[col]) {
+                col += 1;
+                continue;
+            }
+            let start = col;
+            while col < line.len() && is_digit(line[col]) {
+                col += 1;
+            }
+            numbers.push(Number {
+                value: parse_value(&line[start..col]),
+                row,
+                start,
+                end: col,
+            });
+        }
+        numbers
+    }
+
+    pub fn numbers(&self) -> Vec<Number> {
+        (0..self.rows())
+            .flat_map(|row| self.row_numbers(row))
+            .collect()
+    }
+
+    pub fn symbols(&self) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        for (row, line) in self.grid.iter().enumerate() {
+            for (col, &c) in line.iter().enumerate() {
+                if is_symbol(c) {
+                    symbols.push(Symbol { c, row, col });
+                }
+            }
+        }
+        symbols
+    }
+
+    // symbols touching a number, including diagonals
+    pub fn symbols_around(&self, number: &Number) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+        let row_lo = number.row.saturating_sub(1);
+        let row_hi = (number.row + 1).min(self.rows().saturating_sub(1));
+        for row in row_lo..=row_hi {
+            let col_lo = number.start.saturating_sub(1);
+            let col_hi = (number.end + 1).min(self.grid[row].len());
+            for col in col_lo..col_hi {
+                let c = self.grid[row][col];
+                if is_symbol(c) {
+                    symbols.push(Symbol { c, row, col });
+                }
+            }
+        }
+        symbols
+    }
+
+    pub fn graph(&self) -> SchematicGraph {
+        let numbers = self.numbers();
+        let symbols = self.symbols();
+
+        let symbol_idx: HashMap<(usize, usize), usize> = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, s)| ((s.row, s.col), i))
+            .collect();
+
+        let mut number_symbols = vec![Vec::new(); numbers.len()];
+        let mut symbol_numbers = vec![Vec::new(); symbols.len()];
+        for (n, number) in numbers.iter().enumerate() {
+            for symbol in self.symbols_around(number) {
+                let s = symbol_idx[&(symbol.row, symbol.col)];
+                number_symbols[n].push(s);
+                symbol_numbers[s].push(n);
+            }
+        }
+
+        SchematicGraph {
+            numbers,
+            symbols,
+            number_symbols,
+            symbol_numbers,
+        }
+    }
+}
+
+// bipartite adjacency between the numbers and the symbols of a schematic
+#[derive(Debug, Clone)]
+pub struct SchematicGraph {
+    pub numbers: Vec<Number>,
+    pub symbols: Vec<Symbol>,
+    number_symbols: Vec<Vec<usize>>,
+    symbol_numbers: Vec<Vec<usize>>,
+}
+
+impl SchematicGraph {
+    pub fn symbols_of(&self, number: usize) -> &[usize] {
+        &self.number_symbols[number]
+    }
+
+    pub fn numbers_of(&self, symbol: usize) -> &[usize] {
+        &self.symbol_numbers[symbol]
+    }
+
+    // sum of numbers adjacent to at least one symbol accepted by `in_set`
+    pub fn part_sum(&self, in_set: impl Fn(u8) -> bool) -> BigUint {
+        self.numbers
+            .iter()
+            .zip(&self.number_symbols)
+            .filter(|(_, syms)| syms.iter().any(|&s| in_set(self.symbols[s].c)))
+            .map(|(number, _)| &number.value)
+            .sum()
+    }
+
+    // product of the adjacent numbers for every `c` symbol with exactly `k` of them
+    pub fn symbol_products(&self, c: u8, k: usize) -> Vec<BigUint> {
+        self.symbols
+            .iter()
+            .zip(&self.symbol_numbers)
+            .filter(|(symbol, nums)| symbol.c == c && nums.len() == k)
+            .map(|(_, nums)| nums.iter().map(|&n| &self.numbers[n].value).product())
+            .collect()
+    }
+}
+
+// a run of ascii digits
+fn parse_value(digits: &[u8]) -> BigUint {
+    BigUint::parse_bytes(digits, 10).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::Schematic;
+    use crate::lcg::Lcg;
+
+    const EXAMPLE: &str = "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..";
+
+    #[test]
+    fn example_totals() {
+        let schematic = Schematic::parse(EXAMPLE);
+        assert_eq!(*schematic.part_sum(), BigUint::from(4361u32));
+        assert_eq!(*schematic.gear_ratio_total(), BigUint::from(467835u32));
+    }
+
+    #[test]
+    fn long_numbers_keep_exact_totals() {
+        let mut schematic = Schematic::parse("*.........\n1.........");
+        let mut gear = Schematic::parse("7.........\n*.........\n1.........");
+        for _ in 0..25 {
+            schematic.insert(1, 0, b'9');
+            gear.insert(2, 0, b'9');
+        }
+        let value: BigUint = "99999999999999999999999991".parse().unwrap();
+        assert_eq!(*schematic.part_sum(), value);
+        assert_eq!(*gear.part_sum(), &value + 7u32);
+        assert_eq!(*gear.gear_ratio_total(), value * 7u32);
+    }
+
+    #[test]
+    fn incremental_edits_match_full_recompute() {
+        let mut schematic = Schematic::parse(EXAMPLE);
+        let alphabet = b"..........0123456789*#$+";
+
+        let mut lcg = Lcg::new(0x2023_0003);
+        let mut next = |n: usize| lcg.below(n);
+
+        for _ in 0..2000 {
+            let row = next(schematic.rows());
+            let len = schematic.grid[row].len();
+            let c = alphabet[next(alphabet.len())];
+            match next(3) {
+                0 if len > 0 => schematic.set_cell(row, next(len), c),
+                1 => schematic.insert(row, next(len + 1), c),
+                _ if len > 5 => schematic.delete(row, next(len)),
+                _ => schematic.insert(row, next(len + 1), c),
+            }
+
+            let graph = schematic.graph();
+            assert_eq!(*schematic.part_sum(), graph.part_sum(|_| true));
+            assert_eq!(
+                *schematic.gear_ratio_total(),
+                graph.symbol_products(b'*', 2).iter().sum::<BigUint>()
+            );
+        }
+    }
+}
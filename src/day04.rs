@@ -0,0 +1,300 @@
+use std::collections::BTreeMap;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NumberSet {
+    words: Vec<u64>,
+}
+
+impl NumberSet {
+    pub fn insert(&mut self, n: usize) {
+        let word = n / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << (n % 64);
+    }
+
+    pub fn contains(&self, n: usize) -> bool {
+        self.words
+            .get(n / 64)
+            .is_some_and(|w| w & (1 << (n % 64)) != 0)
+    }
+
+    pub fn intersection_len(&self, other: &NumberSet) -> usize {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+}
+
+impl FromIterator<usize> for NumberSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = NumberSet::default();
+        for n in iter {
+            set.insert(n);
+        }
+        set
+    }
+}
+
+// The sets hold indices into `values`, the card's distinct numbers in
+// ascending order, so the bitsets stay a few words long however large the
+// numbers themselves are.
+#[derive(Debug, Clone)]
+pub struct Card {
+    pub id: usize,
+    pub values: Vec<u64>,
+    pub winning: NumberSet,
+    pub numbers: NumberSet,
+}
+
+impl Card {
+    // "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53"
+    pub fn parse(line: &str) -> Card {
+        let (label, nums) = line.split_once(':').unwrap();
+        let (winning, numbers) = nums.split_once('|').unwrap();
+        let parse_list =
+            |s: &str| -> Vec<u64> { s.split_whitespace().map(|n| n.parse().unwrap()).collect() };
+        let (winning, numbers) = (parse_list(winning), parse_list(numbers));
+
+        let mut values: Vec<u64> = winning.iter().chain(&numbers).copied().collect();
+        values.sort_unstable();
+        values.dedup();
+        let dense = |list: &[u64]| -> NumberSet {
+            list.iter()
+                .map(|n| values.binary_search(n).unwrap())
+                .collect()
+        };
+        Card {
+            id: label.trim_start_matches("Card").trim().parse().unwrap(),
+            winning: dense(&winning),
+            numbers: dense(&numbers),
+            values,
+        }
+    }
+
+    pub fn is_winning(&self, n: u64) -> bool {
+        self.values
+            .binary_search(&n)
+            .is_ok_and(|i| self.winning.contains(i))
+    }
+
+    pub fn matches(&self) -> usize {
+        self.winning.intersection_len(&self.numbers)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Scratchcards {
+    pub cards: Vec<Card>,
+}
+
+impl Scratchcards {
+    pub fn parse(input: &str) -> Scratchcards {
+        let cards = input
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .map(Card::parse)
+            .collect();
+        Scratchcards { cards }
+    }
+
+    // how many of each card we end up holding, originals included
+    pub fn copies(&self) -> Vec<BigUint> {
+        let mut copies = vec![BigUint::one(); self.cards.len()];
+        for i in 0..self.cards.len() {
+            let won = self.cards[i].matches();
+            let end = (i + 1 + won).min(self.cards.len());
+            let (held, rest) = copies.split_at_mut(i + 1);
+            for c in &mut rest[..end - i - 1] {
+                *c += &held[i];
+            }
+        }
+        copies
+    }
+
+    pub fn score(&self, rule: &impl ScoringRule) -> BigUint {
+        rule.score(self)
+    }
+}
+
+pub trait ScoringRule {
+    fn score(&self, cards: &Scratchcards) -> BigUint;
+}
+
+// part 1: each original card is worth 1 point, doubled for every match after the first
+pub struct DoublingPoints;
+
+impl ScoringRule for DoublingPoints {
+    fn score(&self, cards: &Scratchcards) -> BigUint {
+        cards
+            .cards
+            .iter()
+            .map(|card| match card.matches() {
+                0 => BigUint::zero(),
+                m => BigUint::one() << (m - 1),
+            })
+            .sum()
+    }
+}
+
+// part 2: total number of cards held once all won copies are cascaded
+pub struct CopyCascade;
+
+impl ScoringRule for CopyCascade {
+    fn score(&self, cards: &Scratchcards) -> BigUint {
+        cards.copies().into_iter().sum()
+    }
+}
+
+// pays `payout(matches)` for every card held after the cascade
+pub struct Payout<F: Fn(usize) -> BigUint>(pub F);
+
+impl<F: Fn(usize) -> BigUint> ScoringRule for Payout<F> {
+    fn score(&self, cards: &Scratchcards) -> BigUint {
+        cards
+            .cards
+            .iter()
+            .zip(cards.copies())
+            .map(|(card, copies)| (self.0)(card.matches()) * copies)
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CardProvenance {
+    pub id: usize,
+    pub copies: BigUint,
+    // copies handed out directly by each earlier card's win, keyed by that card's id
+    pub direct: BTreeMap<usize, BigUint>,
+    // copies traced back to the original card that started their chain
+    pub by_origin: BTreeMap<usize, BigUint>,
+    // longest chain of wins ending at this card, 0 for cards nobody wins
+    pub depth: usize,
+}
+
+impl Scratchcards {
+    // propagates per-origin counts through the cascade instead of tracking
+    // individual copies, so this stays cheap however many copies there are
+    pub fn provenance(&self) -> Vec<CardProvenance> {
+        let mut report: Vec<CardProvenance> = self
+            .cards
+            .iter()
+            .map(|card| CardProvenance {
+                id: card.id,
+                copies: BigUint::one(),
+                direct: BTreeMap::new(),
+                by_origin: BTreeMap::from([(card.id, BigUint::one())]),
+                depth: 0,
+            })
+            .collect();
+
+        for i in 0..self.cards.len() {
+            let end = (i + 1 + self.cards[i].matches()).min(self.cards.len());
+            let (done, rest) = report.split_at_mut(i + 1);
+            let source = &done[i];
+            for target in &mut rest[..end - i - 1] {
+                target.copies += &source.copies;
+                *target.direct.entry(source.id).or_default() += &source.copies;
+                for (origin, count) in &source.by_origin {
+                    *target.by_origin.entry(*origin).or_default() += count;
+                }
+                target.depth = target.depth.max(source.depth + 1);
+            }
+        }
+        report
+    }
+
+    // one row per (card, origin) pair
+    pub fn provenance_csv(&self) -> String {
+        let mut csv = String::from("card,copies,depth,origin,from_origin,direct_from_origin\n");
+        for card in self.provenance() {
+            for (origin, count) in &card.by_origin {
+                let direct = card.direct.get(origin).cloned().unwrap_or_default();
+                csv += &format!(
+                    "{},{},{},{},{},{}\n",
+                    card.id, card.copies, card.depth, origin, count, direct
+                );
+            }
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use num_bigint::BigUint;
+
+    use super::{Card, CopyCascade, DoublingPoints, Payout, Scratchcards};
+
+    const EXAMPLE: &str = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+    #[test]
+    fn example_scores() {
+        let cards = Scratchcards::parse(EXAMPLE);
+        assert_eq!(cards.score(&DoublingPoints), BigUint::from(13u32));
+        assert_eq!(cards.score(&CopyCascade), BigUint::from(30u32));
+        // matches times copies held: 4*1 + 2*2 + 2*4 + 1*8
+        let payout = Payout(|matches| BigUint::from(matches));
+        assert_eq!(cards.score(&payout), BigUint::from(24u32));
+    }
+
+    #[test]
+    fn large_numbers_keep_small_sets() {
+        let card = Card::parse("Card 7: 18446744073709551615 5 | 5 18446744073709551615 7");
+        assert_eq!(card.matches(), 2);
+        assert!(card.is_winning(u64::MAX));
+        assert!(!card.is_winning(7));
+        assert_eq!(card.numbers.intersection_len(&card.numbers), 3);
+    }
+
+    #[test]
+    fn provenance_breaks_copies_down_by_origin() {
+        let cards = Scratchcards::parse(EXAMPLE);
+        let report = cards.provenance();
+        let card4 = &report[3];
+        assert_eq!(card4.copies, BigUint::from(8u32));
+        assert_eq!(card4.depth, 3);
+        let counts = |map: &BTreeMap<usize, BigUint>| -> Vec<(usize, u32)> {
+            map.iter()
+                .map(|(id, n)| (*id, u32::try_from(n).unwrap()))
+                .collect()
+        };
+        assert_eq!(counts(&card4.direct), vec![(1, 1), (2, 2), (3, 4)]);
+        assert_eq!(
+            counts(&card4.by_origin),
+            vec![(1, 4), (2, 2), (3, 1), (4, 1)]
+        );
+        assert_eq!(report[5].depth, 0);
+
+        let csv = cards.provenance_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "card,copies,depth,origin,from_origin,direct_from_origin"
+        );
+        assert_eq!(lines[1], "1,1,0,1,1,0");
+        let card4_rows: Vec<&str> = lines
+            .iter()
+            .filter(|l| l.starts_with("4,"))
+            .copied()
+            .collect();
+        assert_eq!(
+            card4_rows,
+            vec!["4,8,3,1,4,1", "4,8,3,2,2,2", "4,8,3,3,1,4", "4,8,3,4,1,0"]
+        );
+    }
+}
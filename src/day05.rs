@@ -0,0 +1,665 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt;
+
+// (src, dst, s_s, s_e, d_s, d_e), ranges inclusive as in day05part2
+pub type MapTuple = (String, String, u128, u128, u128, u128);
+
+fn shift(x: u128, offset: i128) -> u128 {
+    (x as i128 + offset) as u128
+}
+
+// Sorted pieces covering [0, inf): piece k maps [starts[k], starts[k + 1]) by
+// adding offsets[k]. Every piece is increasing, so the minimum over a run of
+// whole pieces is the minimum of their values at the start, kept in a sparse
+// table for O(1) range queries.
+#[derive(Debug, Clone)]
+pub struct Piecewise {
+    starts: Vec<u128>,
+    offsets: Vec<i128>,
+    min_table: Vec<Vec<u128>>,
+}
+
+impl Piecewise {
+    pub fn identity() -> Piecewise {
+        Piecewise::from_pieces(vec![(0, 0)])
+    }
+
+    fn from_pieces(pieces: Vec<(u128, i128)>) -> Piecewise {
+        let mut starts: Vec<u128> = Vec::new();
+        let mut offsets: Vec<i128> = Vec::new();
+        for (start, offset) in pieces {
+            if offsets.last() == Some(&offset) {
+                continue;
+            }
+            starts.push(start);
+            offsets.push(offset);
+        }
+
+        let mut min_table = vec![starts
+            .iter()
+            .zip(&offsets)
+            .map(|(s, o)| shift(*s, *o))
+            .collect::<Vec<u128>>()];
+        let mut width = 1;
+        while 2 * width <= starts.len() {
+            let prev = min_table.last().unwrap();
+            let level = (0..prev.len() - width)
+                .map(|i| prev[i].min(prev[i + width]))
+                .collect();
+            min_table.push(level);
+            width *= 2;
+        }
+
+        Piecewise {
+            starts,
+            offsets,
+            min_table,
+        }
+    }
+
+    // one map stage; where ranges overlap the one listed first wins
+    pub fn from_ranges(ranges: &[(u128, u128, u128)]) -> Piecewise {
+        let mut bounds = vec![0];
+        for (s_s, s_e, _) in ranges {
+            bounds.push(*s_s);
+            bounds.push(s_e + 1);
+        }
+        bounds.sort();
+        bounds.dedup();
+
+        let pieces = bounds
+            .into_iter()
+            .map(|b| {
+                let offset = ranges
+                    .iter()
+                    .find(|(s_s, s_e, _)| *s_s <= b && b <= *s_e)
+                    .map_or(0, |(s_s, _, d_s)| *d_s as i128 - *s_s as i128);
+                (b, offset)
+            })
+            .collect();
+        Piecewise::from_pieces(pieces)
+    }
+
+    pub fn pieces(&self) -> impl Iterator<Item = (u128, Option<u128>, i128)> + '_ {
+        (0..self.starts.len()).map(|k| {
+            (
+                self.starts[k],
+                self.starts.get(k + 1).copied(),
+                self.offsets[k],
+            )
+        })
+    }
+
+    fn piece_of(&self, x: u128) -> usize {
+        self.starts.partition_point(|s| *s <= x) - 1
+    }
+
+    pub fn apply(&self, x: u128) -> u128 {
+        shift(x, self.offsets[self.piece_of(x)])
+    }
+
+    // x -> next(self(x))
+    pub fn then(&self, next: &Piecewise) -> Piecewise {
+        let mut pieces = Vec::new();
+        for (start, end, offset) in self.pieces() {
+            let image_start = shift(start, offset);
+            pieces.push((start, offset + next.offsets[next.piece_of(image_start)]));
+
+            let first = next.starts.partition_point(|s| *s <= image_start);
+            for k in first..next.starts.len() {
+                let x = (next.starts[k] as i128 - offset) as u128;
+                if end.is_some_and(|e| x >= e) {
+                    break;
+                }
+                pieces.push((x, offset + next.offsets[k]));
+            }
+        }
+        Piecewise::from_pieces(pieces)
+    }
+
+    // minimum of the function over [lo, hi]
+    pub fn min_over(&self, lo: u128, hi: u128) -> u128 {
+        let first = self.piece_of(lo);
+        let last = self.piece_of(hi);
+        let at_lo = self.apply(lo);
+        if first == last {
+            return at_lo;
+        }
+
+        let (l, r) = (first + 1, last);
+        let level = (r - l + 1).ilog2() as usize;
+        let table = &self.min_table[level];
+        at_lo.min(table[l]).min(table[r + 1 - (1 << level)])
+    }
+
+    // the parts of each piece that land in [lo, hi], with that piece's offset
+    pub fn preimage_pieces(&self, lo: u128, hi: u128) -> Vec<(u128, u128, i128)> {
+        let mut ranges = Vec::new();
+        for (start, end, offset) in self.pieces() {
+            let from = (lo as i128 - offset).max(start as i128);
+            let to = match end {
+                Some(end) => (hi as i128 - offset).min(end as i128 - 1),
+                None => hi as i128 - offset,
+            };
+            if from <= to {
+                ranges.push((from as u128, to as u128, offset));
+            }
+        }
+        ranges
+    }
+
+    // every x with lo <= f(x) <= hi, as sorted inclusive ranges
+    pub fn preimage(&self, lo: u128, hi: u128) -> Vec<(u128, u128)> {
+        let mut ranges: Vec<(u128, u128)> = Vec::new();
+        for (from, to, _) in self.preimage_pieces(lo, hi) {
+            match ranges.last_mut() {
+                Some(last) if last.1 + 1 == from => last.1 = to,
+                _ => ranges.push((from, to)),
+            }
+        }
+        ranges
+    }
+}
+
+// a range of `from` values and where it sits in every category up to `to`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trace {
+    pub ranges: Vec<(String, u128, u128)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    Missing {
+        from: String,
+        to: String,
+    },
+    Duplicate {
+        src: String,
+        dst: String,
+    },
+    Cycle(Vec<String>),
+    Overlap {
+        src: String,
+        dst: String,
+        first: (u128, u128),
+        second: (u128, u128),
+    },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::Missing { from, to } => write!(f, "no maps lead from {} to {}", from, to),
+            Diagnostic::Duplicate { src, dst } => {
+                write!(f, "{}-to-{} map appears more than once", src, dst)
+            }
+            Diagnostic::Cycle(categories) => write!(f, "cycle {}", categories.join(" -> ")),
+            Diagnostic::Overlap {
+                src,
+                dst,
+                first,
+                second,
+            } => write!(
+                f,
+                "{}-to-{} map has overlapping source ranges {}..={} and {}..={}",
+                src, dst, first.0, first.1, second.0, second.1
+            ),
+        }
+    }
+}
+
+// one stage per (src, dst) map, any shape of graph
+#[derive(Debug, Clone, Default)]
+pub struct CategoryGraph {
+    edges: BTreeMap<String, BTreeMap<String, Piecewise>>,
+}
+
+impl CategoryGraph {
+    // every header gets an edge, so a map with no range lines is the identity
+    pub fn from_maps(headers: &[(String, String)], maps: &[MapTuple]) -> CategoryGraph {
+        let mut ranges: BTreeMap<(&str, &str), Vec<_>> = headers
+            .iter()
+            .map(|(src, dst)| ((src.as_str(), dst.as_str()), Vec::new()))
+            .collect();
+        for (src, dst, s_s, s_e, d_s, _) in maps {
+            ranges
+                .entry((src, dst))
+                .or_default()
+                .push((*s_s, *s_e, *d_s));
+        }
+
+        let mut graph = CategoryGraph::default();
+        for ((src, dst), ranges) in ranges {
+            graph
+                .edges
+                .entry(src.to_string())
+                .or_default()
+                .insert(dst.to_string(), Piecewise::from_ranges(&ranges));
+        }
+        graph
+    }
+
+    pub fn categories(&self) -> BTreeSet<&str> {
+        self.edges
+            .iter()
+            .flat_map(|(src, dsts)| std::iter::once(src).chain(dsts.keys()))
+            .map(|c| c.as_str())
+            .collect()
+    }
+
+    pub fn stage(&self, src: &str, dst: &str) -> Option<&Piecewise> {
+        self.edges.get(src)?.get(dst)
+    }
+
+    // fewest maps from `from` to `to`
+    pub fn path(&self, from: &str, to: &str) -> Option<Vec<String>> {
+        let mut prev: HashMap<&str, &str> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                let mut path = vec![to.to_string()];
+                let mut at = to;
+                while at != from {
+                    at = prev[at];
+                    path.push(at.to_string());
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for dst in self.edges.get(current).into_iter().flat_map(|d| d.keys()) {
+                if dst != from && !prev.contains_key(dst.as_str()) {
+                    prev.insert(dst, current);
+                    queue.push_back(dst);
+                }
+            }
+        }
+        None
+    }
+
+    // each cycle is reported once, starting and ending at the same category
+    pub fn cycles(&self) -> Vec<Vec<String>> {
+        fn visit<'a>(
+            graph: &'a CategoryGraph,
+            current: &'a str,
+            stack: &mut Vec<&'a str>,
+            done: &mut HashSet<&'a str>,
+            cycles: &mut Vec<Vec<String>>,
+        ) {
+            stack.push(current);
+            for dst in graph.edges.get(current).into_iter().flat_map(|d| d.keys()) {
+                if let Some(pos) = stack.iter().position(|c| c == dst) {
+                    let mut cycle: Vec<String> =
+                        stack[pos..].iter().map(|c| c.to_string()).collect();
+                    cycle.push(dst.clone());
+                    cycles.push(cycle);
+                } else if !done.contains(dst.as_str()) {
+                    visit(graph, dst, stack, done, cycles);
+                }
+            }
+            stack.pop();
+            done.insert(current);
+        }
+
+        let mut cycles = Vec::new();
+        let mut done = HashSet::new();
+        for category in self.categories() {
+            if !done.contains(category) {
+                visit(self, category, &mut Vec::new(), &mut done, &mut cycles);
+            }
+        }
+        cycles
+    }
+
+    // the categories visited going from `from` to `to` and the stage leaving each
+    pub fn chain(
+        &self,
+        from: &str,
+        to: &str,
+    ) -> Result<(Vec<String>, Vec<&Piecewise>), Diagnostic> {
+        let path = self.path(from, to).ok_or_else(|| Diagnostic::Missing {
+            from: from.to_string(),
+            to: to.to_string(),
+        })?;
+        let stages = path
+            .windows(2)
+            .map(|pair| self.stage(&pair[0], &pair[1]).unwrap())
+            .collect();
+        Ok((path, stages))
+    }
+
+    pub fn convert(&self, from: &str, to: &str) -> Result<Piecewise, Diagnostic> {
+        let (_, stages) = self.chain(from, to)?;
+        Ok(stages
+            .into_iter()
+            .fold(Piecewise::identity(), |f, stage| f.then(stage)))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Almanac {
+    pub seeds: Vec<u128>,
+    pub maps: Vec<MapTuple>,
+    // every "src-to-dst map:" header in the order it appeared
+    pub headers: Vec<(String, String)>,
+    pub graph: CategoryGraph,
+}
+
+impl Almanac {
+    pub fn parse(input: &str) -> Almanac {
+        let mut seeds = Vec::new();
+        let mut maps = Vec::new();
+        let mut headers = Vec::new();
+        let mut current_src = String::new();
+        let mut current_dst = String::new();
+        for line in input.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(nums) = line.strip_prefix("seeds:") {
+                seeds = nums
+                    .split_whitespace()
+                    .map(|x| x.parse().unwrap())
+                    .collect();
+            } else if let Some(header) = line.strip_suffix(" map:") {
+                let (src, dst) = header.split_once("-to-").unwrap();
+                current_src = src.to_string();
+                current_dst = dst.to_string();
+                headers.push((current_src.clone(), current_dst.clone()));
+            } else {
+                let nums: Vec<u128> = line
+                    .split_whitespace()
+                    .map(|x| x.parse().unwrap())
+                    .collect();
+                let (d_s, s_s, len) = (nums[0], nums[1], nums[2]);
+                maps.push((
+                    current_src.clone(),
+                    current_dst.clone(),
+                    s_s,
+                    s_s + len - 1,
+                    d_s,
+                    d_s + len - 1,
+                ));
+            }
+        }
+        let graph = CategoryGraph::from_maps(&headers, &maps);
+        Almanac {
+            seeds,
+            maps,
+            headers,
+            graph,
+        }
+    }
+
+    // seeds line read as (start, length) pairs, returned as inclusive ranges
+    pub fn seed_ranges(&self) -> Vec<(u128, u128)> {
+        self.seeds
+            .chunks(2)
+            .map(|pair| (pair[0], pair[0] + pair[1] - 1))
+            .collect()
+    }
+
+    // everything wrong with the maps, plus whether `to` can be reached from `from`
+    pub fn validate(&self, from: &str, to: &str) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        let mut seen = HashSet::new();
+        for (src, dst) in &self.headers {
+            if !seen.insert((src, dst)) {
+                diagnostics.push(Diagnostic::Duplicate {
+                    src: src.clone(),
+                    dst: dst.clone(),
+                });
+            }
+        }
+
+        let mut ranges: BTreeMap<(&str, &str), Vec<(u128, u128)>> = BTreeMap::new();
+        for (src, dst, s_s, s_e, _, _) in &self.maps {
+            ranges.entry((src, dst)).or_default().push((*s_s, *s_e));
+        }
+        for ((src, dst), mut ranges) in ranges {
+            ranges.sort();
+            let mut widest = ranges[0];
+            for range in &ranges[1..] {
+                if range.0 <= widest.1 {
+                    diagnostics.push(Diagnostic::Overlap {
+                        src: src.to_string(),
+                        dst: dst.to_string(),
+                        first: widest,
+                        second: *range,
+                    });
+                }
+                if range.1 > widest.1 {
+                    widest = *range;
+                }
+            }
+        }
+
+        diagnostics.extend(self.graph.cycles().into_iter().map(Diagnostic::Cycle));
+
+        if let Err(missing) = self.graph.chain(from, to) {
+            diagnostics.push(missing);
+        }
+        diagnostics
+    }
+
+    pub fn function(&self, from: &str, to: &str) -> Result<Piecewise, Diagnostic> {
+        self.graph.convert(from, to)
+    }
+
+    // `from` ranges whose `to` value lies in [lo, hi], e.g. all seeds with a
+    // location below some threshold
+    pub fn sources(
+        &self,
+        from: &str,
+        to: &str,
+        lo: u128,
+        hi: u128,
+    ) -> Result<Vec<(u128, u128)>, Diagnostic> {
+        Ok(self.function(from, to)?.preimage(lo, hi))
+    }
+
+    // Walks the chain backwards from [lo, hi] in `to`. Each trace follows a
+    // single piece of every stage, so every category's range is the `from`
+    // range shifted by a constant.
+    pub fn trace_back(
+        &self,
+        from: &str,
+        to: &str,
+        lo: u128,
+        hi: u128,
+    ) -> Result<Vec<Trace>, Diagnostic> {
+        let (categories, stages) = self.graph.chain(from, to)?;
+
+        // (range in the current category, offsets from it to every later one)
+        let mut paths: Vec<(u128, u128, Vec<i128>)> = vec![(lo, hi, Vec::new())];
+        for stage in stages.iter().rev() {
+            let mut prev_paths = Vec::new();
+            for (lo, hi, offsets) in &paths {
+                for (from, to, offset) in stage.preimage_pieces(*lo, *hi) {
+                    let mut prev_offsets = vec![offset];
+                    prev_offsets.extend(offsets.iter().map(|o| o + offset));
+                    prev_paths.push((from, to, prev_offsets));
+                }
+            }
+            paths = prev_paths;
+        }
+        paths.sort();
+
+        let traces = paths
+            .into_iter()
+            .map(|(lo, hi, offsets)| {
+                let ranges = categories
+                    .iter()
+                    .zip(std::iter::once(0).chain(offsets))
+                    .map(|(category, o)| (category.clone(), shift(lo, o), shift(hi, o)))
+                    .collect();
+                Trace { ranges }
+            })
+            .collect();
+        Ok(traces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Almanac, Diagnostic, MapTuple};
+    use crate::lcg::Lcg;
+
+    const CATEGORIES: [&str; 4] = ["seed", "soil", "water", "location"];
+
+    // maps between every pair of neighbouring categories, each with a few
+    // ranges in 0..100 that may overlap; past 100 every stage is the identity
+    fn random_almanac(lcg: &mut Lcg) -> Almanac {
+        let mut input = String::from("seeds: 1 2\n");
+        for pair in CATEGORIES.windows(2) {
+            input += &format!("\n{}-to-{} map:\n", pair[0], pair[1]);
+            for _ in 0..1 + lcg.below(4) {
+                let len = 1 + lcg.below(30);
+                let (src, dst) = (lcg.below(100 - len), lcg.below(100 - len));
+                input += &format!("{} {} {}\n", dst, src, len);
+            }
+        }
+        Almanac::parse(&input)
+    }
+
+    // one stage at a time, the first range that holds x wins
+    fn step(maps: &[MapTuple], src: &str, x: u128) -> u128 {
+        maps.iter()
+            .find(|(s, _, s_s, s_e, _, _)| s == src && *s_s <= x && x <= *s_e)
+            .map_or(x, |(_, _, s_s, _, d_s, _)| d_s + (x - s_s))
+    }
+
+    fn walk(almanac: &Almanac, from: usize, to: usize, x: u128) -> u128 {
+        CATEGORIES[from..to]
+            .iter()
+            .fold(x, |x, src| step(&almanac.maps, src, x))
+    }
+
+    #[test]
+    fn composed_maps_match_stage_by_stage() {
+        let mut lcg = Lcg::new(0x2023_0005);
+        for _ in 0..100 {
+            let almanac = random_almanac(&mut lcg);
+            for (from, to) in [(0, 3), (1, 3), (0, 1)] {
+                let f = almanac.function(CATEGORIES[from], CATEGORIES[to]).unwrap();
+                let values: Vec<u128> = (0..150).map(|x| walk(&almanac, from, to, x)).collect();
+                for (x, value) in values.iter().enumerate() {
+                    assert_eq!(f.apply(x as u128), *value);
+                }
+                for _ in 0..20 {
+                    let lo = lcg.below(150);
+                    let hi = lo + lcg.below(150 - lo);
+                    let min = values[lo..=hi].iter().min().unwrap();
+                    assert_eq!(f.min_over(lo as u128, hi as u128), *min);
+                }
+            }
+        }
+    }
+
+    // sorted inclusive ranges of the xs in 0..limit where `keep` holds
+    fn ranges_where(limit: u128, keep: impl Fn(u128) -> bool) -> Vec<(u128, u128)> {
+        let mut ranges: Vec<(u128, u128)> = Vec::new();
+        for x in (0..limit).filter(|x| keep(*x)) {
+            match ranges.last_mut() {
+                Some(last) if last.1 + 1 == x => last.1 = x,
+                _ => ranges.push((x, x)),
+            }
+        }
+        ranges
+    }
+
+    #[test]
+    fn seeds_traced_back_from_locations() {
+        let mut lcg = Lcg::new(0x2023_0031);
+        for _ in 0..100 {
+            let almanac = random_almanac(&mut lcg);
+            let lo = lcg.below(100) as u128;
+            let hi = lo + lcg.below(100 - lo as usize) as u128;
+            // nothing past 100 maps below it, so 0..200 has every source
+            let wanted = ranges_where(200, |x| (lo..=hi).contains(&walk(&almanac, 0, 3, x)));
+            assert_eq!(almanac.sources("seed", "location", lo, hi).unwrap(), wanted);
+
+            let traces = almanac.trace_back("seed", "location", lo, hi).unwrap();
+            let mut seeds: Vec<(u128, u128)> = Vec::new();
+            for trace in &traces {
+                let (_, seed_lo, seed_hi) = trace.ranges[0];
+                for x in seed_lo..=seed_hi {
+                    for (i, (category, start, _)) in trace.ranges.iter().enumerate() {
+                        assert_eq!(category, CATEGORIES[i]);
+                        assert_eq!(walk(&almanac, 0, i, x), start + (x - seed_lo));
+                    }
+                }
+                match seeds.last_mut() {
+                    Some(last) if last.1 + 1 == seed_lo => last.1 = seed_hi,
+                    _ => seeds.push((seed_lo, seed_hi)),
+                }
+            }
+            assert_eq!(seeds, wanted);
+        }
+    }
+
+    #[test]
+    fn validate_reports_every_problem() {
+        let almanac = Almanac::parse(
+            "seeds: 1 2
+
+seed-to-soil map:
+50 0 10
+60 5 10
+
+soil-to-fertilizer map:
+0 0 5
+
+fertilizer-to-soil map:
+7 7 1
+
+seed-to-soil map:
+0 100 1",
+        );
+        assert_eq!(
+            almanac.validate("seed", "location"),
+            vec![
+                Diagnostic::Duplicate {
+                    src: "seed".to_string(),
+                    dst: "soil".to_string(),
+                },
+                Diagnostic::Overlap {
+                    src: "seed".to_string(),
+                    dst: "soil".to_string(),
+                    first: (0, 9),
+                    second: (5, 14),
+                },
+                Diagnostic::Cycle(vec![
+                    "fertilizer".to_string(),
+                    "soil".to_string(),
+                    "fertilizer".to_string(),
+                ]),
+                Diagnostic::Missing {
+                    from: "seed".to_string(),
+                    to: "location".to_string(),
+                },
+            ]
+        );
+        // the cycle doesn't stop a conversion that exists
+        let f = almanac.function("seed", "fertilizer").unwrap();
+        assert_eq!(f.apply(0), 50);
+        assert_eq!(f.apply(100), 0);
+    }
+
+    #[test]
+    fn empty_maps_are_the_identity() {
+        let almanac = Almanac::parse(
+            "seeds: 1 2
+
+seed-to-soil map:
+
+soil-to-location map:
+1 0 1",
+        );
+        assert_eq!(almanac.validate("seed", "location"), vec![]);
+        let f = almanac.function("seed", "location").unwrap();
+        assert_eq!((f.apply(0), f.apply(5)), (1, 5));
+    }
+}
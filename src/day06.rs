@@ -0,0 +1,309 @@
+use num_bigint::BigUint;
+use num_traits::One;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Race {
+    pub time: BigUint,
+    pub record: BigUint,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wins {
+    // hold times beating the record are exactly first..=last
+    pub first: BigUint,
+    pub last: BigUint,
+    pub count: BigUint,
+    pub best_hold: BigUint,
+}
+
+fn numbers(line: &str) -> impl Iterator<Item = &str> {
+    line.split(':').nth(1).unwrap().split_whitespace()
+}
+
+// one race per column
+pub fn parse_races(input: &str) -> Vec<Race> {
+    let lines: Vec<&str> = input.lines().collect();
+    numbers(lines[0])
+        .zip(numbers(lines[1]))
+        .map(|(time, record)| Race {
+            time: time.parse().unwrap(),
+            record: record.parse().unwrap(),
+        })
+        .collect()
+}
+
+// the columns are one number with bad kerning
+pub fn parse_single_race(input: &str) -> Race {
+    let lines: Vec<&str> = input.lines().collect();
+    Race {
+        time: numbers(lines[0]).collect::<String>().parse().unwrap(),
+        record: numbers(lines[1]).collect::<String>().parse().unwrap(),
+    }
+}
+
+impl Race {
+    pub fn distance(&self, hold: &BigUint) -> BigUint {
+        if hold > &self.time {
+            return BigUint::default();
+        }
+        hold * (&self.time - hold)
+    }
+
+    pub fn beats_record(&self, hold: &BigUint) -> bool {
+        self.distance(hold) > self.record
+    }
+
+    // h * (t - h) > r  <=>  h lies strictly between (t -/+ sqrt(t^2 - 4r)) / 2;
+    // start from the integer square root and nudge onto the exact boundary
+    pub fn wins(&self) -> Option<Wins> {
+        let best_hold = &self.time / 2u32;
+        if !self.beats_record(&best_hold) {
+            return None;
+        }
+
+        let root = (&self.time * &self.time - &self.record * 4u32).sqrt();
+        let mut first = (&self.time - root) / 2u32;
+        while !self.beats_record(&first) {
+            first += 1u32;
+        }
+        while first > BigUint::default() && self.beats_record(&(&first - 1u32)) {
+            first -= 1u32;
+        }
+
+        let last = &self.time - &first;
+        let count = &last - &first + BigUint::one();
+        Some(Wins {
+            first,
+            last,
+            count,
+            best_hold,
+        })
+    }
+
+    pub fn ways_to_win(&self) -> BigUint {
+        self.wins().map(|w| w.count).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    RecordsBeaten,
+    ProductOfWays,
+}
+
+// charge is the longest the button may be held in that race; ways counts the
+// hold times up to it that still beat the record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Allocation {
+    pub charge: BigUint,
+    pub ways: BigUint,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plan {
+    pub allocations: Vec<Allocation>,
+    pub records_beaten: usize,
+    pub ways_product: BigUint,
+}
+
+impl Plan {
+    fn from_charges(races: &[Race], charges: Vec<BigUint>) -> Plan {
+        let allocations: Vec<Allocation> = races
+            .iter()
+            .zip(charges)
+            .map(|(race, charge)| {
+                let ways = match race.wins() {
+                    Some(w) if charge >= w.first => charge.clone().min(w.last) - w.first + 1u32,
+                    _ => BigUint::default(),
+                };
+                Allocation { charge, ways }
+            })
+            .collect();
+        let records_beaten = allocations
+            .iter()
+            .filter(|a| a.ways > BigUint::default())
+            .count();
+        let ways_product = allocations.iter().map(|a| &a.ways).product();
+        Plan {
+            allocations,
+            records_beaten,
+            ways_product,
+        }
+    }
+}
+
+// split `budget` of charging time across the races
+pub fn plan(races: &[Race], budget: &BigUint, objective: Objective) -> Plan {
+    let wins: Vec<Option<Wins>> = races.iter().map(|race| race.wins()).collect();
+    let mut charges = vec![BigUint::default(); races.len()];
+
+    match objective {
+        // cheapest races first
+        Objective::RecordsBeaten => {
+            let mut order: Vec<usize> = (0..races.len()).filter(|i| wins[*i].is_some()).collect();
+            order.sort_by_key(|i| wins[*i].as_ref().unwrap().first.clone());
+
+            let mut left = budget.clone();
+            for i in order {
+                let first = &wins[i].as_ref().unwrap().first;
+                if *first > left {
+                    break;
+                }
+                left -= first;
+                charges[i] = first.clone();
+            }
+        }
+        // Every race has to reach its first winning hold, after that the
+        // product is largest when the ways are as level as their caps allow:
+        // find the highest level w the leftover budget can fill, then hand out
+        // the remainder one at a time to races that can still go above it.
+        Objective::ProductOfWays => {
+            let needed: BigUint = wins.iter().flatten().map(|w| &w.first).sum();
+            if wins.iter().any(|w| w.is_none()) || needed > *budget {
+                return Plan::from_charges(races, charges);
+            }
+            let left = budget - needed;
+            let caps: Vec<BigUint> = wins.iter().flatten().map(|w| w.count.clone()).collect();
+            let cost =
+                |level: &BigUint| -> BigUint { caps.iter().map(|cap| cap.min(level) - 1u32).sum() };
+
+            let mut lo = BigUint::one();
+            let mut hi = caps.iter().max().cloned().unwrap_or_else(BigUint::one);
+            while lo < hi {
+                let mid = (&lo + &hi + 1u32) / 2u32;
+                if cost(&mid) <= left {
+                    lo = mid;
+                } else {
+                    hi = mid - 1u32;
+                }
+            }
+
+            let mut spare = left - cost(&lo);
+            for (i, w) in wins.iter().flatten().enumerate() {
+                let mut ways = caps[i].clone().min(lo.clone());
+                if ways < caps[i] && spare > BigUint::default() {
+                    ways += 1u32;
+                    spare -= 1u32;
+                }
+                charges[i] = &w.first + ways - 1u32;
+            }
+        }
+    }
+    Plan::from_charges(races, charges)
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::{parse_races, parse_single_race, plan, Objective, Race};
+    use crate::lcg::Lcg;
+
+    const EXAMPLE: &str = "Time:      7  15   30
+Distance:  9  40  200";
+
+    #[test]
+    fn example_ways() {
+        let product: BigUint = parse_races(EXAMPLE)
+            .iter()
+            .map(|r| r.ways_to_win())
+            .product();
+        assert_eq!(product, BigUint::from(288u32));
+        let race = parse_single_race(EXAMPLE);
+        assert_eq!(race.ways_to_win(), BigUint::from(71503u32));
+    }
+
+    #[test]
+    fn wins_match_trying_every_hold() {
+        let mut lcg = Lcg::new(0x2023_0006);
+        for _ in 0..500 {
+            let time = lcg.below(40) as u32;
+            let race = Race {
+                time: time.into(),
+                record: (lcg.below(time as usize * time as usize / 4 + 2) as u32).into(),
+            };
+            let winning: Vec<BigUint> = (0..=time + 1)
+                .map(BigUint::from)
+                .filter(|hold| race.beats_record(hold))
+                .collect();
+            match race.wins() {
+                None => assert!(winning.is_empty(), "{:?}", race),
+                Some(wins) => {
+                    assert_eq!(Some(&wins.first), winning.first());
+                    assert_eq!(Some(&wins.last), winning.last());
+                    assert_eq!(wins.count, BigUint::from(winning.len()));
+                    let best = (0..=time).map(|h| race.distance(&h.into())).max();
+                    assert_eq!(Some(race.distance(&wins.best_hold)), best);
+                }
+            }
+        }
+    }
+
+    // every way to split at most `budget` across the races, by brute force:
+    // (records beaten, product of ways) for the best split under each
+    fn best_splits(races: &[Race], budget: u32) -> (usize, BigUint) {
+        // ways[i][c]: winning holds no longer than c in race i
+        let ways: Vec<Vec<u32>> = races
+            .iter()
+            .map(|race| {
+                let mut count = 0;
+                (0..=budget)
+                    .map(|c| {
+                        count += u32::from(race.beats_record(&c.into()));
+                        count
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut best = (0, BigUint::default());
+        let mut charges = vec![0; races.len()];
+        loop {
+            if charges.iter().sum::<u32>() <= budget {
+                let won: Vec<u32> = (0..races.len())
+                    .map(|i| ways[i][charges[i] as usize])
+                    .collect();
+                best.0 = best.0.max(won.iter().filter(|w| **w > 0).count());
+                best.1 = best.1.max(won.iter().map(|w| BigUint::from(*w)).product());
+            }
+            let Some(i) = charges.iter().position(|c| *c < budget) else {
+                return best;
+            };
+            charges[i] += 1;
+            charges[..i].fill(0);
+        }
+    }
+
+    #[test]
+    fn plans_match_brute_force() {
+        let mut lcg = Lcg::new(0x2023_0034);
+        for _ in 0..200 {
+            let races: Vec<Race> = (0..1 + lcg.below(3))
+                .map(|_| {
+                    let time = 1 + lcg.below(12);
+                    Race {
+                        time: time.into(),
+                        record: lcg.below(time * time / 4 + 2).into(),
+                    }
+                })
+                .collect();
+            let budget = lcg.below(16) as u32;
+            let (records, product) = best_splits(&races, budget);
+            for objective in [Objective::RecordsBeaten, Objective::ProductOfWays] {
+                let plan = plan(&races, &budget.into(), objective);
+                let spent: BigUint = plan.allocations.iter().map(|a| &a.charge).sum();
+                assert!(spent <= budget.into(), "{:?} {:?}", races, plan);
+                for (race, allocation) in races.iter().zip(&plan.allocations) {
+                    let charge: u32 = (&allocation.charge).try_into().unwrap();
+                    let ways = (0..=charge)
+                        .filter(|h| race.beats_record(&(*h).into()))
+                        .count();
+                    assert_eq!(allocation.ways, BigUint::from(ways));
+                }
+                match objective {
+                    Objective::RecordsBeaten => assert_eq!(plan.records_beaten, records),
+                    Objective::ProductOfWays => assert_eq!(plan.ways_product, product),
+                }
+            }
+        }
+    }
+}
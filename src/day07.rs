@@ -0,0 +1,595 @@
+use std::collections::{BTreeMap, HashMap};
+
+use num_bigint::BigUint;
+use num_rational::Ratio;
+use num_traits::{One, Zero};
+
+// index into Rules::categories, higher is stronger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HandType(pub usize);
+
+// A hand is in a category if, after the wildcards pick their rank (and
+// suit), it holds groups of at least these sizes, and is a flush / straight
+// when asked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Category {
+    pub name: String,
+    pub groups: Vec<usize>,
+    pub flush: bool,
+    pub straight: bool,
+}
+
+impl Category {
+    pub fn groups(name: &str, groups: &[usize]) -> Category {
+        Category {
+            name: name.to_string(),
+            groups: groups.to_vec(),
+            flush: false,
+            straight: false,
+        }
+    }
+
+    pub fn flush(mut self) -> Category {
+        self.flush = true;
+        self
+    }
+
+    pub fn straight(mut self) -> Category {
+        self.straight = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    // card by card in the order dealt, as in the puzzle
+    Positional,
+    // bigger groups first, then higher ranks first, like poker
+    GroupsThenRank,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Card {
+    pub rank: char,
+    pub suit: Option<char>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rules {
+    // weakest first
+    pub card_order: Vec<char>,
+    pub wildcards: Vec<char>,
+    // weakest first, the first one should match any hand
+    pub categories: Vec<Category>,
+    pub tie_break: TieBreak,
+    // cards are written rank then suit, e.g. "AhKh9s9d2c"
+    pub suited: bool,
+}
+
+impl Rules {
+    fn camel_categories() -> Vec<Category> {
+        vec![
+            Category::groups("High card", &[]),
+            Category::groups("One pair", &[2]),
+            Category::groups("Two pair", &[2, 2]),
+            Category::groups("Three of a kind", &[3]),
+            Category::groups("Full house", &[3, 2]),
+            Category::groups("Four of a kind", &[4]),
+            Category::groups("Five of a kind", &[5]),
+        ]
+    }
+
+    pub fn part1() -> Rules {
+        Rules {
+            card_order: "23456789TJQKA".chars().collect(),
+            wildcards: Vec::new(),
+            categories: Rules::camel_categories(),
+            tie_break: TieBreak::Positional,
+            suited: false,
+        }
+    }
+
+    pub fn part2() -> Rules {
+        Rules {
+            card_order: "J23456789TQKA".chars().collect(),
+            wildcards: vec!['J'],
+            ..Rules::part1()
+        }
+    }
+
+    pub fn category(&self, hand_type: HandType) -> &Category {
+        &self.categories[hand_type.0]
+    }
+
+    pub fn strength(&self, rank: char) -> u8 {
+        self.card_order.iter().position(|c| *c == rank).unwrap() as u8
+    }
+
+    pub fn parse_hand(&self, hand: &str) -> Vec<Card> {
+        let chars: Vec<char> = hand.chars().collect();
+        if self.suited {
+            chars
+                .chunks(2)
+                .map(|c| Card {
+                    rank: c[0],
+                    suit: Some(c[1]),
+                })
+                .collect()
+        } else {
+            chars
+                .into_iter()
+                .map(|rank| Card { rank, suit: None })
+                .collect()
+        }
+    }
+
+    // Without suits every card compares equal on suit, so every hand would
+    // be a flush; rules asking for one have to be suited.
+    fn check_flushes(&self) {
+        assert!(
+            self.suited || self.categories.iter().all(|c| !c.flush),
+            "flush categories need suited rules"
+        );
+    }
+
+    pub fn classify(&self, cards: &[Card]) -> HandType {
+        self.check_flushes();
+        let wild = cards
+            .iter()
+            .filter(|c| self.wildcards.contains(&c.rank))
+            .count();
+        let rest: Vec<&Card> = cards
+            .iter()
+            .filter(|c| !self.wildcards.contains(&c.rank))
+            .collect();
+
+        let mut counts: HashMap<char, usize> = HashMap::new();
+        for card in &rest {
+            *counts.entry(card.rank).or_default() += 1;
+        }
+        let mut groups: Vec<usize> = counts.values().copied().collect();
+        groups.sort_by(|a, b| b.cmp(a));
+
+        let is_flush = rest.windows(2).all(|w| w[0].suit == w[1].suit);
+        let is_straight = groups.iter().all(|g| *g == 1) && {
+            let positions: Vec<u8> = rest.iter().map(|c| self.strength(c.rank)).collect();
+            match (positions.iter().min(), positions.iter().max()) {
+                (Some(lo), Some(hi)) => ((hi - lo) as usize) < cards.len(),
+                _ => true,
+            }
+        };
+
+        HandType(best_category(
+            &self.categories,
+            &groups,
+            wild,
+            is_flush,
+            is_straight,
+        ))
+    }
+
+    // card strengths in the order they are compared on a category tie
+    pub fn tie_break_ranks(&self, cards: &[Card]) -> Vec<u8> {
+        let mut ranks: Vec<u8> = cards.iter().map(|c| self.strength(c.rank)).collect();
+        if self.tie_break == TieBreak::GroupsThenRank {
+            let mut counts: HashMap<u8, usize> = HashMap::new();
+            for r in &ranks {
+                *counts.entry(*r).or_default() += 1;
+            }
+            ranks.sort_by(|a, b| (counts[b], b).cmp(&(counts[a], a)));
+        }
+        ranks
+    }
+
+    pub fn key(&self, hand: &str) -> (HandType, Vec<u8>) {
+        let cards = self.parse_hand(hand);
+        (self.classify(&cards), self.tie_break_ranks(&cards))
+    }
+
+    pub fn ranker(&self) -> Ranker<'_> {
+        Ranker::new(self)
+    }
+
+    // bid times rank, weakest hand is rank 1
+    pub fn total_winnings(&self, hands: &[(&str, u64)]) -> u64 {
+        self.ranker().total_winnings(hands)
+    }
+}
+
+// strongest category the hand can reach, `groups` sorted biggest first
+fn best_category(
+    categories: &[Category],
+    groups: &[usize],
+    wild: usize,
+    is_flush: bool,
+    is_straight: bool,
+) -> usize {
+    for (i, category) in categories.iter().enumerate().rev() {
+        // match the biggest groups we have to the biggest ones wanted,
+        // wildcards make up the shortfall
+        let shortfall: usize = category
+            .groups
+            .iter()
+            .enumerate()
+            .map(|(j, want)| want.saturating_sub(*groups.get(j).unwrap_or(&0)))
+            .sum();
+        if shortfall <= wild && (!category.flush || is_flush) && (!category.straight || is_straight)
+        {
+            return i;
+        }
+    }
+    0
+}
+
+pub const MAX_PACKED_CARDS: usize = 7;
+
+// strength of a card that isn't in the card order
+const UNKNOWN_CARD: u8 = u8::MAX;
+
+// Rules turned into byte lookup tables. key() packs the category and the
+// tie-break ranks into a single u64, 8 bits each with the category on top, so
+// comparing keys is the same as comparing Rules::key and nothing needs to be
+// re-derived while sorting.
+pub struct Ranker<'a> {
+    rules: &'a Rules,
+    strength: [u8; 256],
+    wild: [bool; 256],
+}
+
+impl<'a> Ranker<'a> {
+    pub fn new(rules: &'a Rules) -> Ranker<'a> {
+        rules.check_flushes();
+        assert!(
+            rules.card_order.len() < UNKNOWN_CARD as usize,
+            "too many cards to pack"
+        );
+        assert!(
+            rules.categories.len() <= u8::MAX as usize,
+            "too many categories to pack"
+        );
+        let mut strength = [UNKNOWN_CARD; 256];
+        let mut wild = [false; 256];
+        for (i, c) in rules.card_order.iter().enumerate() {
+            assert!(c.is_ascii(), "packed keys need ascii cards");
+            strength[*c as usize] = i as u8;
+            wild[*c as usize] = rules.wildcards.contains(c);
+        }
+        Ranker {
+            rules,
+            strength,
+            wild,
+        }
+    }
+
+    pub fn key(&self, hand: &str) -> u64 {
+        let bytes = hand.as_bytes();
+        let step = if self.rules.suited { 2 } else { 1 };
+        let n = bytes.len() / step;
+        assert!(n <= MAX_PACKED_CARDS, "hand too long to pack: {}", hand);
+
+        let mut ranks = [0u8; MAX_PACKED_CARDS];
+        let mut counts = [0u8; 256];
+        let mut all_counts = [0u8; 256];
+        let mut wild = 0;
+        let mut suit = None;
+        let mut is_flush = true;
+        let (mut lo, mut hi) = (u8::MAX, 0);
+        for i in 0..n {
+            let c = bytes[i * step] as usize;
+            let s = self.strength[c];
+            assert!(
+                s != UNKNOWN_CARD,
+                "unknown card {} in {}",
+                c as u8 as char,
+                hand
+            );
+            ranks[i] = s;
+            all_counts[s as usize] += 1;
+            if self.wild[c] {
+                wild += 1;
+                continue;
+            }
+            counts[s as usize] += 1;
+            lo = lo.min(s);
+            hi = hi.max(s);
+            if step == 2 {
+                let this_suit = bytes[i * step + 1];
+                is_flush &= *suit.get_or_insert(this_suit) == this_suit;
+            }
+        }
+
+        let mut groups = [0usize; MAX_PACKED_CARDS];
+        let mut k = 0;
+        for r in &ranks[..n] {
+            let count = &mut counts[*r as usize];
+            if *count > 0 {
+                groups[k] = *count as usize;
+                *count = 0;
+                k += 1;
+            }
+        }
+        let groups = &mut groups[..k];
+        groups.sort_unstable_by(|a, b| b.cmp(a));
+        let is_straight = groups.iter().all(|g| *g == 1) && (k == 0 || ((hi - lo) as usize) < n);
+
+        let category = best_category(&self.rules.categories, groups, wild, is_flush, is_straight);
+
+        let ranks = &mut ranks[..n];
+        if self.rules.tie_break == TieBreak::GroupsThenRank {
+            ranks.sort_unstable_by(|a, b| {
+                (all_counts[*b as usize], b).cmp(&(all_counts[*a as usize], a))
+            });
+        }
+        let mut key = (category as u64) << (8 * MAX_PACKED_CARDS);
+        for (i, r) in ranks.iter().enumerate() {
+            key |= (*r as u64) << (8 * (MAX_PACKED_CARDS - 1 - i));
+        }
+        key
+    }
+
+    pub fn hand_type(key: u64) -> HandType {
+        HandType((key >> (8 * MAX_PACKED_CARDS)) as usize)
+    }
+
+    // indices of `hands` from weakest to strongest, equal hands keep their order
+    pub fn rank(&self, hands: &[(&str, u64)]) -> Vec<usize> {
+        let mut keyed: Vec<u128> = hands
+            .iter()
+            .enumerate()
+            .map(|(i, (hand, _))| (self.key(hand) as u128) << 64 | i as u128)
+            .collect();
+        keyed.sort_unstable();
+        keyed.into_iter().map(|k| k as u64 as usize).collect()
+    }
+
+    pub fn total_winnings(&self, hands: &[(&str, u64)]) -> u64 {
+        self.rank(hands)
+            .into_iter()
+            .enumerate()
+            .map(|(i, h)| (i as u64 + 1) * hands[h].1)
+            .sum()
+    }
+}
+
+pub fn parse_hands(input: &str) -> Vec<(&str, u64)> {
+    input
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(hand, bid)| (hand, bid.trim().parse().unwrap()))
+        .collect()
+}
+
+// the cards still left to draw from, with how many of each
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deck {
+    pub cards: Vec<(Card, u64)>,
+}
+
+impl Deck {
+    pub fn uniform(ranks: &[char], copies: u64) -> Deck {
+        let cards = ranks
+            .iter()
+            .map(|rank| {
+                (
+                    Card {
+                        rank: *rank,
+                        suit: None,
+                    },
+                    copies,
+                )
+            })
+            .collect();
+        Deck { cards }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.cards.iter().map(|(_, n)| n).sum()
+    }
+
+    // takes out the cards already in hand
+    pub fn without(&self, known: &[Card]) -> Deck {
+        let mut deck = self.clone();
+        for card in known {
+            if let Some((_, n)) = deck.cards.iter_mut().find(|(c, _)| c == card) {
+                *n = n.saturating_sub(1);
+            }
+        }
+        deck
+    }
+}
+
+// all empty, with a zero total, when no draw is possible at all
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Outcomes {
+    // number of equally likely draws ending in each hand type
+    pub ways: BTreeMap<HandType, BigUint>,
+    pub total: BigUint,
+}
+
+impl Outcomes {
+    pub fn probability(&self, hand_type: HandType) -> Ratio<BigUint> {
+        if self.total.is_zero() {
+            return Ratio::from_integer(BigUint::zero());
+        }
+        let ways = self.ways.get(&hand_type).cloned().unwrap_or_default();
+        Ratio::new(ways, self.total.clone())
+    }
+}
+
+fn binomial(n: u64, k: u64) -> BigUint {
+    if k > n {
+        return BigUint::zero();
+    }
+    (0..k).fold(BigUint::one(), |acc, i| acc * (n - i) / (i + 1))
+}
+
+impl Rules {
+    // Fills the hand up to `hand_size` with cards drawn without replacement.
+    // Hand types don't depend on card order, so this walks the multisets of
+    // draws, each weighted by how many ways the deck can produce it.
+    pub fn outcomes(&self, known: &[Card], deck: &Deck, hand_size: usize) -> Outcomes {
+        fn draw(
+            rules: &Rules,
+            deck: &[(Card, u64)],
+            left: usize,
+            hand: &mut Vec<Card>,
+            weight: BigUint,
+            ways: &mut BTreeMap<HandType, BigUint>,
+        ) {
+            if left == 0 {
+                *ways.entry(rules.classify(hand)).or_default() += weight;
+                return;
+            }
+            let Some(((card, available), rest)) = deck.split_first() else {
+                return;
+            };
+            for taken in 0..=left.min(*available as usize) {
+                hand.extend(std::iter::repeat_n(*card, taken));
+                let weight = &weight * binomial(*available, taken as u64);
+                draw(rules, rest, left - taken, hand, weight, ways);
+                hand.truncate(hand.len() - taken);
+            }
+        }
+
+        // nothing can be drawn: too many cards known, or too few in the deck
+        let Some(to_draw) = hand_size.checked_sub(known.len()) else {
+            return Outcomes::default();
+        };
+        if deck.size() < to_draw as u64 {
+            return Outcomes::default();
+        }
+        let mut ways = BTreeMap::new();
+        draw(
+            self,
+            &deck.cards,
+            to_draw,
+            &mut known.to_vec(),
+            BigUint::one(),
+            &mut ways,
+        );
+        Outcomes {
+            ways,
+            total: binomial(deck.size(), to_draw as u64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+    use num_rational::Ratio;
+    use num_traits::Zero;
+
+    use super::{parse_hands, Category, Deck, HandType, Outcomes, Ranker, Rules, TieBreak};
+    use crate::lcg::Lcg;
+
+    const EXAMPLE: &str = "32T3K 765
+T55J5 684
+KK677 28
+KTJJT 220
+QQQJA 483";
+
+    #[test]
+    fn example_winnings() {
+        let hands = parse_hands(EXAMPLE);
+        assert_eq!(Rules::part1().total_winnings(&hands), 6440);
+        assert_eq!(Rules::part2().total_winnings(&hands), 5905);
+    }
+
+    #[test]
+    #[should_panic(expected = "flush categories need suited rules")]
+    fn unsuited_flush_is_rejected() {
+        let mut rules = Rules::part1();
+        rules
+            .categories
+            .push(Category::groups("Flush", &[]).flush());
+        rules.key("32T3K");
+    }
+
+    // poker with deuces wild
+    fn wild_poker() -> Rules {
+        Rules {
+            card_order: "23456789TJQKA".chars().collect(),
+            wildcards: vec!['2'],
+            categories: vec![
+                Category::groups("High card", &[]),
+                Category::groups("One pair", &[2]),
+                Category::groups("Two pair", &[2, 2]),
+                Category::groups("Three of a kind", &[3]),
+                Category::groups("Straight", &[]).straight(),
+                Category::groups("Flush", &[]).flush(),
+                Category::groups("Full house", &[3, 2]),
+                Category::groups("Four of a kind", &[4]),
+                Category::groups("Straight flush", &[]).straight().flush(),
+                Category::groups("Five of a kind", &[5]),
+            ],
+            tie_break: TieBreak::GroupsThenRank,
+            suited: true,
+        }
+    }
+
+    #[test]
+    fn packed_keys_order_like_rules_keys() {
+        let mut lcg = Lcg::new(0x2023_0007);
+        for rules in [Rules::part1(), Rules::part2(), wild_poker()] {
+            let ranker = rules.ranker();
+            // few ranks and two suits, so groups, straights and flushes all turn up
+            let ranks = &rules.card_order[lcg.below(4)..][..6];
+            let hands: Vec<String> = (0..400)
+                .map(|_| {
+                    (0..5)
+                        .flat_map(|_| {
+                            let rank = ranks[lcg.below(ranks.len())];
+                            let suit = ['h', 's'][lcg.below(2)];
+                            match rules.suited {
+                                true => vec![rank, suit],
+                                false => vec![rank],
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            let keys: Vec<_> = hands
+                .iter()
+                .map(|h| (ranker.key(h), rules.key(h)))
+                .collect();
+            for (hand, (packed, key)) in hands.iter().zip(&keys) {
+                assert_eq!(Ranker::hand_type(*packed), key.0, "{}", hand);
+            }
+            for a in &keys {
+                for b in &keys {
+                    assert_eq!(a.0.cmp(&b.0), a.1.cmp(&b.1), "{:?} vs {:?}", a.1, b.1);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn outcomes_of_three_aces() {
+        // two cards from the 49 left: the last ace and anything makes four of
+        // a kind (48 ways), a pair of another rank a full house (12 * 6), and
+        // everything else stays three of a kind
+        let rules = Rules::part1();
+        let known = rules.parse_hand("AAA");
+        let deck = Deck::uniform(&rules.card_order, 4).without(&known);
+        let outcomes = rules.outcomes(&known, &deck, 5);
+        assert_eq!(outcomes.total, BigUint::from(1176u32));
+        assert_eq!(outcomes.ways.values().sum::<BigUint>(), outcomes.total);
+        let ways: Vec<u32> = outcomes
+            .ways
+            .values()
+            .map(|w| w.try_into().unwrap())
+            .collect();
+        assert_eq!(ways, vec![1056, 72, 48]);
+        assert_eq!(
+            outcomes.probability(HandType(5)),
+            Ratio::new(BigUint::from(2u32), BigUint::from(49u32))
+        );
+        assert_eq!(
+            outcomes.probability(HandType(6)),
+            Ratio::from_integer(BigUint::zero())
+        );
+        // more cards known than the hand holds
+        assert_eq!(rules.outcomes(&known, &deck, 2), Outcomes::default());
+    }
+}
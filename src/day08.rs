@@ -0,0 +1,458 @@
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt;
+
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_traits::{Signed, Zero};
+
+#[derive(Debug, Clone)]
+pub struct Network<'a> {
+    pub instructions: &'a [u8],
+    pub nodes: HashMap<&'a str, (&'a str, &'a str)>,
+}
+
+impl<'a> Network<'a> {
+    // "LLR" then lines like "AAA = (BBB, CCC)"
+    pub fn parse(input: &'a str) -> Network<'a> {
+        let mut lines = input.lines();
+        let instructions = lines.next().unwrap().trim().as_bytes();
+        let nodes = lines
+            .filter_map(|line| line.split_once('='))
+            .map(|(src, dst)| {
+                let dst = dst.trim().trim_start_matches('(').trim_end_matches(')');
+                let (l, r) = dst.split_once(',').unwrap();
+                (src.trim(), (l.trim(), r.trim()))
+            })
+            .collect();
+        Network {
+            instructions,
+            nodes,
+        }
+    }
+
+    // node names become indices; a target that is never defined gets a
+    // placeholder node that loops onto itself, kept for diagnostics()
+    pub fn compile(&self) -> Compiled<'a> {
+        let mut names: Vec<&'a str> = self.nodes.keys().copied().collect();
+        names.sort();
+        let mut index: HashMap<&'a str, usize> =
+            names.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+        let mut missing = Vec::new();
+        let mut left = Vec::new();
+        let mut right = Vec::new();
+        let mut i = 0;
+        while i < names.len() {
+            let node = names[i];
+            let (l, r) = self.nodes.get(node).copied().unwrap_or((node, node));
+            for (target, table) in [(l, &mut left), (r, &mut right)] {
+                let idx = *index.entry(target).or_insert_with(|| {
+                    missing.push(Diagnostic::MissingTarget {
+                        node: node.to_string(),
+                        target: target.to_string(),
+                    });
+                    names.push(target);
+                    names.len() - 1
+                });
+                table.push(idx);
+            }
+            i += 1;
+        }
+
+        Compiled::new(self.instructions, names, index, left, right, missing)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    MissingTarget { node: String, target: String },
+    Unreachable { node: String },
+    NeverReachesTarget { start: String },
+    NoPath { from: String, to: String },
+}
+
+impl Error for Diagnostic {}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::MissingTarget { node, target } => {
+                write!(f, "{} points at {}, which is not defined", node, target)
+            }
+            Diagnostic::Unreachable { node } => {
+                write!(f, "{} can't be reached from any start", node)
+            }
+            Diagnostic::NeverReachesTarget { start } => {
+                write!(f, "{} never gets to a ..Z node", start)
+            }
+            Diagnostic::NoPath { from, to } => write!(f, "{} never gets to {}", from, to),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Compiled<'a> {
+    pub names: Vec<&'a str>,
+    pub index: HashMap<&'a str, usize>,
+    instructions: Vec<bool>,
+    left: Vec<usize>,
+    right: Vec<usize>,
+    // passes[k][n]: where n ends up after 2^k full passes over the instructions
+    passes: Vec<Vec<usize>>,
+    missing: Vec<Diagnostic>,
+}
+
+impl<'a> Compiled<'a> {
+    fn new(
+        instructions: &[u8],
+        names: Vec<&'a str>,
+        index: HashMap<&'a str, usize>,
+        left: Vec<usize>,
+        right: Vec<usize>,
+        missing: Vec<Diagnostic>,
+    ) -> Compiled<'a> {
+        let instructions = instructions
+            .iter()
+            .map(|i| match i {
+                b'L' => false,
+                b'R' => true,
+                instruction => panic!("oh no! {instruction}"),
+            })
+            .collect();
+        let mut compiled = Compiled {
+            names,
+            index,
+            instructions,
+            left,
+            right,
+            passes: Vec::new(),
+            missing,
+        };
+
+        let one_pass = (0..compiled.names.len())
+            .map(|n| (0..compiled.instructions.len()).fold(n, |n, i| compiled.step(n, i)))
+            .collect();
+        compiled.passes.push(one_pass);
+        for _ in 1..u64::BITS {
+            let prev = compiled.passes.last().unwrap();
+            let next = prev.iter().map(|n| prev[*n]).collect();
+            compiled.passes.push(next);
+        }
+        compiled
+    }
+
+    pub fn step(&self, node: usize, instruction_idx: usize) -> usize {
+        if self.instructions[instruction_idx] {
+            self.right[node]
+        } else {
+            self.left[node]
+        }
+    }
+
+    pub fn pass(&self, node: usize) -> usize {
+        self.passes[0][node]
+    }
+
+    // node reached after `steps` single steps, jumping whole passes at a time
+    pub fn advance(&self, mut node: usize, steps: u64) -> usize {
+        let len = self.instructions.len() as u64;
+        let passes = steps / len;
+        for (k, table) in self.passes.iter().enumerate() {
+            if passes >> k & 1 == 1 {
+                node = table[node];
+            }
+        }
+        (0..(steps % len) as usize).fold(node, |n, i| self.step(n, i))
+    }
+
+    pub fn starts(&self) -> Vec<usize> {
+        (0..self.names.len())
+            .filter(|n| self.names[*n].ends_with('A'))
+            .collect()
+    }
+
+    pub fn targets(&self) -> Vec<bool> {
+        self.names.iter().map(|n| n.ends_with('Z')).collect()
+    }
+
+    // undefined targets, nodes no ghost can reach and ghosts that never get
+    // anywhere; walks every ghost's cycle, so only worth it when asked for
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = self.missing.clone();
+
+        let mut reached = vec![false; self.names.len()];
+        let mut stack = self.starts();
+        while let Some(n) = stack.pop() {
+            if !std::mem::replace(&mut reached[n], true) {
+                stack.push(self.left[n]);
+                stack.push(self.right[n]);
+            }
+        }
+        for (n, name) in self.names.iter().enumerate() {
+            if !reached[n] {
+                diagnostics.push(Diagnostic::Unreachable {
+                    node: name.to_string(),
+                });
+            }
+        }
+
+        let targets = self.targets();
+        for start in self.starts() {
+            if self.cycle(start, &targets).hits.is_empty() {
+                diagnostics.push(Diagnostic::NeverReachesTarget {
+                    start: self.names[start].to_string(),
+                });
+            }
+        }
+        diagnostics
+    }
+
+    // Walks (node, instruction index) states from `start` until one repeats.
+    // From then on the walk is periodic, so the target hits seen up to that
+    // point describe every hit there will ever be.
+    pub fn cycle(&self, start: usize, is_target: &[bool]) -> Cycle {
+        let len = self.instructions.len();
+        let mut seen = vec![usize::MAX; self.names.len() * len];
+        let mut hits = Vec::new();
+        let mut node = start;
+        let mut t = 0;
+        loop {
+            let idx = t % len;
+            let state = node * len + idx;
+            if seen[state] != usize::MAX {
+                return Cycle {
+                    pre_period: seen[state],
+                    period: t - seen[state],
+                    hits,
+                };
+            }
+            seen[state] = t;
+            if is_target[node] {
+                hits.push(t);
+            }
+            node = self.step(node, idx);
+            t += 1;
+        }
+    }
+
+    // every ghost starts on a ..A node and wants to be on a ..Z node
+    pub fn ghost_cycles(&self) -> Vec<Cycle> {
+        let targets = self.targets();
+        self.starts()
+            .into_iter()
+            .map(|start| self.cycle(start, &targets))
+            .collect()
+    }
+}
+
+// steps t at which a walk is on a target: `hits` lists all of them below
+// pre_period + period, and each hit at or after pre_period repeats every period
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub pre_period: usize,
+    pub period: usize,
+    pub hits: Vec<usize>,
+}
+
+impl Cycle {
+    pub fn hits_at(&self, t: usize) -> bool {
+        if t < self.pre_period {
+            return self.hits.binary_search(&t).is_ok();
+        }
+        let t = self.pre_period + (t - self.pre_period) % self.period;
+        self.hits.binary_search(&t).is_ok()
+    }
+
+    fn residues(&self) -> BTreeSet<usize> {
+        self.hits
+            .iter()
+            .filter(|h| **h >= self.pre_period)
+            .map(|h| h % self.period)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Synchronisation {
+    At(BigUint),
+    Never,
+}
+
+impl fmt::Display for Synchronisation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Synchronisation::At(t) => write!(f, "{}", t),
+            Synchronisation::Never => write!(f, "never synchronises"),
+        }
+    }
+}
+
+// x = a1 mod m1 and x = a2 mod m2, for moduli that need not be coprime
+fn crt(a1: &BigInt, m1: &BigInt, a2: &BigInt, m2: &BigInt) -> Option<(BigInt, BigInt)> {
+    let gcd = m1.extended_gcd(m2);
+    let diff = a2 - a1;
+    if !(&diff % &gcd.gcd).is_zero() {
+        return None;
+    }
+    let lcm = m1 / &gcd.gcd * m2;
+    let k = (diff / &gcd.gcd * gcd.x).mod_floor(&(m2 / &gcd.gcd));
+    Some(((a1 + m1 * k).mod_floor(&lcm), lcm))
+}
+
+// first step at which every walk is on a target at once; with no walks at
+// all there is nothing to synchronise
+pub fn synchronise(cycles: &[Cycle]) -> Synchronisation {
+    if cycles.is_empty() {
+        return Synchronisation::Never;
+    }
+    // before the last walk settles into its loop just try every step
+    let settled = cycles.iter().map(|c| c.pre_period).max().unwrap_or(0);
+    for t in 0..settled {
+        if cycles.iter().all(|c| c.hits_at(t)) {
+            return Synchronisation::At(t.into());
+        }
+    }
+
+    // afterwards each walk hits on a set of residues mod its period; combine
+    // one residue from every walk at a time
+    let mut solutions: BTreeSet<BigInt> = BTreeSet::from([BigInt::zero()]);
+    let mut modulus = BigInt::from(1);
+    for cycle in cycles {
+        let period = BigInt::from(cycle.period);
+        let mut next = BTreeSet::new();
+        let mut next_modulus = modulus.clone();
+        for x in &solutions {
+            for r in cycle.residues() {
+                if let Some((y, lcm)) = crt(x, &modulus, &BigInt::from(r), &period) {
+                    next.insert(y);
+                    next_modulus = lcm;
+                }
+            }
+        }
+        if next.is_empty() {
+            return Synchronisation::Never;
+        }
+        solutions = next;
+        modulus = next_modulus;
+    }
+
+    // smallest t >= settled in any of the residue classes
+    let settled = BigInt::from(settled);
+    let first = solutions
+        .iter()
+        .map(|x| {
+            let behind = &settled - x;
+            if behind.is_positive() {
+                x + behind.div_ceil(&modulus) * &modulus
+            } else {
+                x.clone()
+            }
+        })
+        .min()
+        .unwrap();
+    Synchronisation::At(first.to_biguint().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{synchronise, Network, Synchronisation};
+    use crate::lcg::Lcg;
+
+    // first step at which every ghost is on a ..Z node, walking one step at a time
+    fn brute_force(input: &str, limit: usize) -> Option<usize> {
+        let network = Network::parse(input);
+        let mut ghosts: Vec<&str> = network
+            .nodes
+            .keys()
+            .copied()
+            .filter(|n| n.ends_with('A'))
+            .collect();
+        for t in 0..limit {
+            if ghosts.iter().all(|g| g.ends_with('Z')) {
+                return Some(t);
+            }
+            let right = network.instructions[t % network.instructions.len()] == b'R';
+            for g in &mut ghosts {
+                let (l, r) = network.nodes[g];
+                *g = if right { r } else { l };
+            }
+        }
+        None
+    }
+
+    fn solve(input: &str) -> Synchronisation {
+        let network = Network::parse(input).compile();
+        synchronise(&network.ghost_cycles())
+    }
+
+    #[test]
+    fn never_synchronises() {
+        // 11A is on 11Z at odd steps, 22A only at even ones after a pre-period
+        let input = "L
+
+11A = (11Z, 11Z)
+11Z = (11A, 11A)
+22A = (22B, 22B)
+22B = (22Z, 22Z)
+22Z = (22B, 22B)";
+        assert_eq!(solve(input), Synchronisation::Never);
+        assert_eq!(brute_force(input, 1000), None);
+    }
+
+    #[test]
+    fn no_ghosts_never_synchronise() {
+        assert_eq!(solve("LR\n\nBBB = (BBB, BBB)"), Synchronisation::Never);
+    }
+
+    #[test]
+    fn pre_period() {
+        // 33A walks into the C/Z loop after two steps, so hits start at 3
+        let input = "L
+
+11A = (11Z, 11Z)
+11Z = (11A, 11A)
+33A = (33B, 33B)
+33B = (33C, 33C)
+33C = (33Z, 33Z)
+33Z = (33C, 33C)";
+        assert_eq!(solve(input), Synchronisation::At(3u32.into()));
+        assert_eq!(brute_force(input, 1000), Some(3));
+    }
+
+    #[test]
+    fn random_networks_match_brute_force() {
+        let mut lcg = Lcg::new(0x2023_0008);
+        let mut next = |n: usize| lcg.below(n);
+
+        for _ in 0..300 {
+            let len = 1 + next(4);
+            let instructions: String = (0..len).map(|_| ['L', 'R'][next(2)]).collect();
+            // at most three ghosts, at least one so there is something to walk
+            let size = 2 + next(7);
+            let names: Vec<String> = (0..size)
+                .map(|i| {
+                    let suffix = match (i, next(3)) {
+                        (0, _) | (1..=2, 0) => 'A',
+                        (_, 1) => 'Z',
+                        _ => 'B',
+                    };
+                    format!("{:02}{}", i, suffix)
+                })
+                .collect();
+            let mut input = format!("{}\n\n", instructions);
+            for name in &names {
+                let (l, r) = (&names[next(size)], &names[next(size)]);
+                input += &format!("{} = ({}, {})\n", name, l, r);
+            }
+
+            // ghosts settle within size * len steps and repeat within that
+            // many more, so three of them meet within 32 + 32^3 if ever
+            let brute = brute_force(&input, 40_000);
+            match solve(&input) {
+                Synchronisation::At(t) => assert_eq!(Some(t), brute.map(Into::into), "{}", input),
+                Synchronisation::Never => assert_eq!(brute, None, "{}", input),
+            }
+        }
+    }
+}
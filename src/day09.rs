@@ -0,0 +1,334 @@
+use std::error::Error;
+use std::fmt;
+
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+// with 1-based line numbers, blank lines skipped
+pub fn parse_histories(input: &str) -> Vec<(usize, Vec<BigInt>)> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(i, line)| {
+            let history = line
+                .split_whitespace()
+                .map(|num| num.parse().unwrap())
+                .collect();
+            (i, history)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Classification {
+    Polynomial(Polynomial),
+    // a single value (or none) fits every degree
+    TooShort { len: usize },
+    // the differences never reach a row of zeros before running out of values
+    NotPolynomial { len: usize },
+}
+
+impl Classification {
+    pub fn of(history: &[BigInt]) -> Classification {
+        let len = history.len();
+        if len < 2 {
+            return Classification::TooShort { len };
+        }
+        let polynomial = Polynomial::fit(history);
+        // the zero row must still have an entry left to be seen at all
+        if polynomial.diffs.len() >= len {
+            return Classification::NotPolynomial { len };
+        }
+        Classification::Polynomial(polynomial)
+    }
+}
+
+impl fmt::Display for Classification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Classification::Polynomial(p) => match p.degree() {
+                Some(degree) => write!(f, "polynomial of degree {}", degree),
+                None => write!(f, "all zeros"),
+            },
+            Classification::TooShort { len } => {
+                write!(f, "too short to tell, only {} values", len)
+            }
+            Classification::NotPolynomial { len } => {
+                write!(f, "not a polynomial within its {} values", len)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsupported {
+    pub lines: Vec<(usize, Classification)>,
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} histories can't be extrapolated:", self.lines.len())?;
+        for (line, classification) in &self.lines {
+            write!(f, "\n  line {}: {}", line, classification)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for Unsupported {}
+
+// every history with its polynomial, or all the ones that don't have one
+pub fn fit_histories(input: &str) -> Result<Vec<(Vec<BigInt>, Polynomial)>, Unsupported> {
+    let mut fits = Vec::new();
+    let mut unsupported = Vec::new();
+    for (line, history) in parse_histories(input) {
+        match Classification::of(&history) {
+            Classification::Polynomial(p) => fits.push((history, p)),
+            other => unsupported.push((line, other)),
+        }
+    }
+    if !unsupported.is_empty() {
+        return Err(Unsupported { lines: unsupported });
+    }
+    Ok(fits)
+}
+
+// The polynomial through a history, in Newton form over the history index n:
+// p(n) = sum_k diffs[k] * C(n, k), where diffs[k] is the first entry of the
+// k-th difference row. Only rows up to the first all-zero one are kept, which
+// gives the minimal degree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    pub diffs: Vec<BigInt>,
+}
+
+impl Polynomial {
+    pub fn fit(history: &[BigInt]) -> Polynomial {
+        let mut diffs = Vec::new();
+        let mut row = history.to_vec();
+        while row.iter().any(|v| !v.is_zero()) {
+            diffs.push(row[0].clone());
+            row = row.windows(2).map(|w| &w[1] - &w[0]).collect();
+        }
+        Polynomial { diffs }
+    }
+
+    pub fn degree(&self) -> Option<usize> {
+        self.diffs.len().checked_sub(1)
+    }
+
+    // O(degree): C(n, k) follows from C(n, k - 1), and stays an integer for
+    // negative n too
+    pub fn at(&self, n: &BigInt) -> BigInt {
+        let mut value = BigInt::zero();
+        let mut binomial = BigInt::one();
+        for (k, diff) in self.diffs.iter().enumerate() {
+            if k > 0 {
+                binomial = binomial * (n - (k - 1)) / k;
+            }
+            value += diff * &binomial;
+        }
+        value
+    }
+
+    // `steps` past the end of a history of length `len`, negative to go back
+    // before its start
+    pub fn extrapolate(&self, len: usize, steps: i64) -> BigInt {
+        let n = if steps >= 0 {
+            BigInt::from(len - 1) + steps
+        } else {
+            BigInt::from(steps)
+        };
+        self.at(&n)
+    }
+
+    // monomial coefficients, constant term first
+    pub fn coefficients(&self) -> Vec<BigRational> {
+        let mut coefficients = vec![BigRational::zero(); self.diffs.len().max(1)];
+        // falling factorial n (n - 1) ... (n - k + 1) / k!, built up term by term
+        let mut basis = vec![BigRational::one()];
+        for (k, diff) in self.diffs.iter().enumerate() {
+            if k > 0 {
+                let mut next = vec![BigRational::zero(); basis.len() + 1];
+                let shift = BigRational::from_integer(BigInt::from(k - 1));
+                for (i, c) in basis.iter().enumerate() {
+                    next[i + 1] += c;
+                    next[i] -= c * &shift;
+                }
+                let k = BigRational::from_integer(BigInt::from(k));
+                basis = next.into_iter().map(|c| c / &k).collect();
+            }
+            for (i, c) in basis.iter().enumerate() {
+                coefficients[i] += c * BigRational::from_integer(diff.clone());
+            }
+        }
+        coefficients
+    }
+}
+
+// e.g. "p(n) = 1/2 n^2 + 3/2 n + 1", n counting from 0 at the first value
+impl fmt::Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "p(n) =")?;
+        let mut first = true;
+        for (power, c) in self.coefficients().iter().enumerate().rev() {
+            if c.is_zero() && !(first && power == 0) {
+                continue;
+            }
+            let sign = match (first, c.is_negative()) {
+                (true, true) => " -",
+                (true, false) => " ",
+                (false, true) => " - ",
+                (false, false) => " + ",
+            };
+            let magnitude = c.abs();
+            let variable = match power {
+                0 => String::new(),
+                1 => "n".to_string(),
+                _ => format!("n^{}", power),
+            };
+            if power > 0 && magnitude.is_one() {
+                write!(f, "{}{}", sign, variable)?;
+            } else if power > 0 {
+                write!(f, "{}{} {}", sign, magnitude, variable)?;
+            } else {
+                write!(f, "{}{}", sign, magnitude)?;
+            }
+            first = false;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigInt;
+    use num_rational::BigRational;
+    use num_traits::Zero;
+
+    use super::{fit_histories, Classification, Polynomial};
+    use crate::lcg::Lcg;
+
+    const EXAMPLE: [[i64; 6]; 3] = [
+        [0, 3, 6, 9, 12, 15],
+        [1, 3, 6, 10, 15, 21],
+        [10, 13, 16, 21, 30, 45],
+    ];
+
+    fn history(values: &[i64]) -> Vec<BigInt> {
+        values.iter().map(|&v| BigInt::from(v)).collect()
+    }
+
+    #[test]
+    fn example_extrapolates_both_ways() {
+        let fits: Vec<Polynomial> = EXAMPLE
+            .iter()
+            .map(|values| Polynomial::fit(&history(values)))
+            .collect();
+        let sum = |steps| -> BigInt { fits.iter().map(|p| p.extrapolate(6, steps)).sum() };
+        assert_eq!(sum(1), BigInt::from(114));
+        assert_eq!(sum(-1), BigInt::from(2));
+    }
+
+    #[test]
+    fn closed_forms_print_in_lowest_terms() {
+        let display = |values: &[i64]| Polynomial::fit(&history(values)).to_string();
+        assert_eq!(display(&[0, 3, 6, 9, 12, 15]), "p(n) = 3 n");
+        assert_eq!(
+            display(&[1, 3, 6, 10, 15, 21]),
+            "p(n) = 1/2 n^2 + 3/2 n + 1"
+        );
+        assert_eq!(
+            display(&[10, 13, 16, 21, 30, 45]),
+            "p(n) = 1/3 n^3 - n^2 + 11/3 n + 10"
+        );
+        assert_eq!(display(&[0, -1, -2]), "p(n) = -n");
+        assert_eq!(display(&[-4, -4]), "p(n) = -4");
+        assert_eq!(display(&[0, 0, 0]), "p(n) = 0");
+    }
+
+    #[test]
+    fn random_polynomials_are_recovered() {
+        let mut lcg = Lcg::new(0x2023_0009);
+        for _ in 0..300 {
+            // integer coefficients, constant term first
+            let coefficients: Vec<BigInt> = (0..=lcg.below(6))
+                .map(|_| BigInt::from(lcg.below(41) as i64 - 20))
+                .collect();
+            let eval = |n: i64| -> BigInt {
+                coefficients
+                    .iter()
+                    .rev()
+                    .fold(BigInt::zero(), |acc, c| acc * n + c)
+            };
+            let len = coefficients.len() + 1 + lcg.below(4);
+            let values: Vec<BigInt> = (0..len as i64).map(eval).collect();
+            let p = Polynomial::fit(&values);
+
+            let degree = coefficients.iter().rposition(|c| !c.is_zero());
+            assert_eq!(p.degree(), degree, "{:?}", coefficients);
+            let expected: Vec<BigRational> = coefficients[..degree.map_or(1, |d| d + 1)]
+                .iter()
+                .map(|c| BigRational::from_integer(c.clone()))
+                .collect();
+            assert_eq!(p.coefficients(), expected, "{:?}", coefficients);
+            for n in -10..len as i64 + 10 {
+                assert_eq!(
+                    p.at(&BigInt::from(n)),
+                    eval(n),
+                    "{:?} at {}",
+                    coefficients,
+                    n
+                );
+            }
+            assert_eq!(p.extrapolate(len, 3), eval(len as i64 + 2));
+            assert_eq!(p.extrapolate(len, -3), eval(-3));
+
+            // one value short of the zero row leaves the degree undecided
+            if let Some(short) = degree.filter(|&d| d > 0).map(|d| d + 1) {
+                assert_eq!(
+                    Classification::of(&values[..short]),
+                    Classification::NotPolynomial { len: short }
+                );
+            }
+            assert_eq!(Classification::of(&values), Classification::Polynomial(p));
+        }
+    }
+
+    #[test]
+    fn unsupported_lines_are_reported_by_number() {
+        assert_eq!(
+            Classification::of(&history(&[7])),
+            Classification::TooShort { len: 1 }
+        );
+        assert_eq!(Classification::of(&[]), Classification::TooShort { len: 0 });
+        assert_eq!(
+            Classification::of(&history(&[1, 2, 4, 8])),
+            Classification::NotPolynomial { len: 4 }
+        );
+
+        // blank lines are skipped but still counted
+        let input = "0 3 6 9 12 15\n\n7\n1 2 4 8\n1 3 6 10 15 21\n";
+        let unsupported = fit_histories(input).unwrap_err();
+        assert_eq!(
+            unsupported.lines,
+            vec![
+                (3, Classification::TooShort { len: 1 }),
+                (4, Classification::NotPolynomial { len: 4 }),
+            ]
+        );
+        assert_eq!(
+            unsupported.to_string(),
+            "2 histories can't be extrapolated:\n  \
+             line 3: too short to tell, only 1 values\n  \
+             line 4: not a polynomial within its 4 values"
+        );
+
+        let fits = fit_histories("0 3 6 9 12 15\n\n1 3 6 10 15 21\n").unwrap();
+        assert_eq!(fits.len(), 2);
+        assert_eq!(fits[1].1.to_string(), "p(n) = 1/2 n^2 + 3/2 n + 1");
+    }
+}
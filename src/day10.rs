@@ -0,0 +1,651 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+
+    // (dy, dx)
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::East => (0, 1),
+            Direction::South => (1, 0),
+            Direction::West => (0, -1),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum PipeType {
+    NS,
+    EW,
+    NE,
+    NW,
+    SW,
+    SE,
+    GROUND,
+    START,
+}
+
+impl PipeType {
+    pub const PIPES: [PipeType; 6] = [
+        PipeType::NS,
+        PipeType::EW,
+        PipeType::NE,
+        PipeType::NW,
+        PipeType::SW,
+        PipeType::SE,
+    ];
+
+    pub fn from_char(c: char) -> PipeType {
+        match c {
+            '|' => PipeType::NS,
+            '-' => PipeType::EW,
+            'L' => PipeType::NE,
+            'J' => PipeType::NW,
+            '7' => PipeType::SW,
+            'F' => PipeType::SE,
+            '.' => PipeType::GROUND,
+            'S' => PipeType::START,
+            _ => panic!("Unknown pipe type!"),
+        }
+    }
+
+    // the two ends of a real pipe; ground and the start have none of their own
+    pub fn ends(self) -> Option<[Direction; 2]> {
+        use Direction::*;
+        match self {
+            PipeType::NS => Some([North, South]),
+            PipeType::EW => Some([East, West]),
+            PipeType::NE => Some([North, East]),
+            PipeType::NW => Some([North, West]),
+            PipeType::SW => Some([South, West]),
+            PipeType::SE => Some([South, East]),
+            PipeType::GROUND | PipeType::START => None,
+        }
+    }
+
+    pub fn connects(self, dir: Direction) -> bool {
+        self.ends().is_some_and(|ends| ends.contains(&dir))
+    }
+
+    pub fn from_ends(a: Direction, b: Direction) -> Option<PipeType> {
+        PipeType::PIPES
+            .into_iter()
+            .find(|pipe| pipe.connects(a) && pipe.connects(b) && a != b)
+    }
+}
+
+impl PipeType {
+    pub fn glyph(self) -> char {
+        match self {
+            PipeType::NS => '│',
+            PipeType::EW => '─',
+            PipeType::NE => '└',
+            PipeType::NW => '┘',
+            PipeType::SW => '┐',
+            PipeType::SE => '┌',
+            PipeType::GROUND => '·',
+            PipeType::START => '╳',
+        }
+    }
+}
+
+impl fmt::Display for PipeType {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(match self {
+            PipeType::NS => "|",
+            PipeType::EW => "-",
+            PipeType::NE => "L",
+            PipeType::NW => "J",
+            PipeType::SW => "7",
+            PipeType::SE => "F",
+            PipeType::GROUND => ".",
+            PipeType::START => "S",
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PipeMap {
+    pub pipes: Vec<Vec<PipeType>>,
+    // generated maps may have no `S` at all
+    pub start: Option<(usize, usize)>,
+}
+
+impl PipeMap {
+    pub fn parse(input: &str) -> PipeMap {
+        let pipes: Vec<Vec<PipeType>> = input
+            .trim()
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(PipeType::from_char)
+                    .collect()
+            })
+            .collect();
+        let start = pipes.iter().enumerate().find_map(|(y, row)| {
+            row.iter()
+                .position(|p| *p == PipeType::START)
+                .map(|x| (y, x))
+        });
+        PipeMap { pipes, start }
+    }
+
+    pub fn height(&self) -> usize {
+        self.pipes.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.pipes.first().map_or(0, |row| row.len())
+    }
+
+    pub fn type_at(&self, (y, x): (usize, usize)) -> PipeType {
+        self.pipes[y][x]
+    }
+
+    pub fn neighbour(&self, (y, x): (usize, usize), dir: Direction) -> Option<(usize, usize)> {
+        let (dy, dx) = dir.delta();
+        let y = y.checked_add_signed(dy).filter(|y| *y < self.height())?;
+        let x = x.checked_add_signed(dx).filter(|x| *x < self.width())?;
+        Some((y, x))
+    }
+
+    // the tile `dir` of `point`, if the pipes on both sides meet there; the
+    // start counts as open in every direction
+    pub fn connected(&self, point: (usize, usize), dir: Direction) -> Option<(usize, usize)> {
+        let opens = |p: (usize, usize), d: Direction| {
+            let pipe = self.type_at(p);
+            pipe == PipeType::START || pipe.connects(d)
+        };
+        let next = self.neighbour(point, dir)?;
+        (opens(point, dir) && opens(next, dir.opposite())).then_some(next)
+    }
+
+    // the pipe hiding under `S`, if exactly two neighbours connect to it
+    pub fn start_shape(&self) -> Option<PipeType> {
+        let start = self.start?;
+        let ends: Vec<Direction> = Direction::ALL
+            .into_iter()
+            .filter(|dir| self.connected(start, *dir).is_some())
+            .collect();
+        match ends[..] {
+            [a, b] => PipeType::from_ends(a, b),
+            _ => None,
+        }
+    }
+
+    // the pipe on a tile, with `S` resolved to its inferred shape
+    pub fn shape_at(&self, point: (usize, usize)) -> Option<PipeType> {
+        match self.type_at(point) {
+            PipeType::START => self.start_shape(),
+            PipeType::GROUND => None,
+            pipe => Some(pipe),
+        }
+    }
+
+    // Follows pipes from `from` along the first end of its shape. Every pipe
+    // has exactly two ends, so the walk either comes back round to `from` or
+    // runs into a dead end; the tiles passed are returned either way.
+    fn walk(&self, from: (usize, usize)) -> (Vec<(usize, usize)>, bool) {
+        let Some(shape) = self.shape_at(from) else {
+            return (vec![from], false);
+        };
+        let mut tiles = vec![from];
+        let mut dir = shape.ends().unwrap()[0];
+        let mut point = from;
+        loop {
+            let Some(next) = self.connected(point, dir) else {
+                return (tiles, false);
+            };
+            if next == from {
+                return (tiles, true);
+            }
+            tiles.push(next);
+            let Some([a, b]) = self.shape_at(next).and_then(|p| p.ends()) else {
+                return (tiles, false);
+            };
+            dir = if a == dir.opposite() { b } else { a };
+            point = next;
+        }
+    }
+
+    fn loop_from(&self, from: (usize, usize)) -> Option<PipeLoop> {
+        match self.walk(from) {
+            (tiles, true) => Some(PipeLoop {
+                tiles,
+                start_shape: self.shape_at(from).unwrap(),
+            }),
+            (_, false) => None,
+        }
+    }
+
+    // the loop through `S`, walked from the start along the first end of its
+    // inferred shape
+    pub fn main_loop(&self) -> Option<PipeLoop> {
+        self.loop_from(self.start?)
+    }
+
+    // every closed loop on the map, each starting from its first tile in
+    // reading order
+    pub fn loops(&self) -> Vec<PipeLoop> {
+        let mut seen = vec![vec![false; self.width()]; self.height()];
+        let mut loops = Vec::new();
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                if seen[y][x] || self.shape_at((y, x)).is_none() {
+                    continue;
+                }
+                // a walk that doesn't close is a broken chain, and none of
+                // the tiles on it can be part of a loop either
+                let (tiles, closed) = self.walk((y, x));
+                for &(ty, tx) in &tiles {
+                    seen[ty][tx] = true;
+                }
+                if closed {
+                    loops.push(PipeLoop {
+                        tiles,
+                        start_shape: self.shape_at((y, x)).unwrap(),
+                    });
+                }
+            }
+        }
+        loops
+    }
+
+    // For each loop, the index of the smallest other loop around it. One
+    // scan over the rows: crossing a pipe that connects north moves in or out
+    // of its loop, and as loops never cross, the loops around the current
+    // tile form a stack with the innermost on top. A loop's first tile is
+    // where it is met first, so whatever is on top there encloses it.
+    pub fn nesting(&self, loops: &[PipeLoop]) -> Vec<Option<usize>> {
+        let mut owner = vec![vec![None; self.width()]; self.height()];
+        for (i, pipe_loop) in loops.iter().enumerate() {
+            for &(y, x) in &pipe_loop.tiles {
+                owner[y][x] = Some(i);
+            }
+        }
+        let mut parents = vec![None; loops.len()];
+        for (y, row) in owner.iter().enumerate() {
+            let mut around: Vec<usize> = Vec::new();
+            for (x, tile) in row.iter().enumerate() {
+                let Some(i) = *tile else {
+                    continue;
+                };
+                if loops[i].tiles[0] == (y, x) {
+                    parents[i] = around.last().copied();
+                }
+                if self.shape_at((y, x)).unwrap().connects(Direction::North) {
+                    if around.last() == Some(&i) {
+                        around.pop();
+                    } else {
+                        around.push(i);
+                    }
+                }
+            }
+        }
+        parents
+    }
+
+    // pipe ends that lead off the map or into a tile that doesn't connect back
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        if let Some(start) = self.start {
+            if self.start_shape().is_none() {
+                let open = Direction::ALL
+                    .into_iter()
+                    .filter(|dir| self.connected(start, *dir).is_some())
+                    .count();
+                diagnostics.push(Diagnostic::UnresolvedStart { tile: start, open });
+            }
+        }
+        for y in 0..self.height() {
+            for x in 0..self.width() {
+                let Some(shape) = self.shape_at((y, x)) else {
+                    continue;
+                };
+                for dir in shape.ends().unwrap() {
+                    let tile = (y, x);
+                    match self.neighbour(tile, dir) {
+                        None => diagnostics.push(Diagnostic::OffMap { tile, dir }),
+                        Some(target) if self.connected(tile, dir).is_none() => {
+                            diagnostics.push(Diagnostic::Dangling { tile, dir, target })
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+        diagnostics
+    }
+
+    // Flood fill on a 3x scaled grid where every loop tile becomes a 3x3 block
+    // with its pipe drawn through the middle, so the outside can squeeze
+    // between touching pipes. Tiles whose centre stays unreached are inside.
+    pub fn regions(&self, pipe_loop: &PipeLoop) -> Vec<Vec<Region>> {
+        let (h, w) = (self.height() * 3, self.width() * 3);
+        let mut wall = vec![vec![false; w]; h];
+        for &(y, x) in &pipe_loop.tiles {
+            let (cy, cx) = (3 * y + 1, 3 * x + 1);
+            wall[cy][cx] = true;
+            for dir in self.shape_at((y, x)).unwrap().ends().unwrap() {
+                let (dy, dx) = dir.delta();
+                wall[cy.wrapping_add_signed(dy)][cx.wrapping_add_signed(dx)] = true;
+            }
+        }
+
+        let mut outside = vec![vec![false; w]; h];
+        let mut stack: Vec<(usize, usize)> = (0..h)
+            .flat_map(|y| [(y, 0), (y, w - 1)])
+            .chain((0..w).flat_map(|x| [(0, x), (h - 1, x)]))
+            .collect();
+        while let Some((y, x)) = stack.pop() {
+            if wall[y][x] || std::mem::replace(&mut outside[y][x], true) {
+                continue;
+            }
+            for dir in Direction::ALL {
+                let (dy, dx) = dir.delta();
+                let ny = y.wrapping_add_signed(dy);
+                let nx = x.wrapping_add_signed(dx);
+                if ny < h && nx < w {
+                    stack.push((ny, nx));
+                }
+            }
+        }
+
+        let mut regions: Vec<Vec<Region>> = (0..self.height())
+            .map(|y| {
+                (0..self.width())
+                    .map(|x| match outside[3 * y + 1][3 * x + 1] {
+                        true => Region::Outside,
+                        false => Region::Inside,
+                    })
+                    .collect()
+            })
+            .collect();
+        for &(y, x) in &pipe_loop.tiles {
+            regions[y][x] = Region::Loop;
+        }
+        regions
+    }
+
+    pub fn enclosed_by_flood(&self, pipe_loop: &PipeLoop) -> usize {
+        self.regions(pipe_loop)
+            .iter()
+            .flatten()
+            .filter(|r| **r == Region::Inside)
+            .count()
+    }
+
+    // Box-drawing glyphs with ANSI colours: the loop in bold yellow (its start
+    // in red), other pipes dimmed, and every other tile shaded green inside
+    // the loop or grey outside it. View with a terminal or `less -R`.
+    pub fn render(&self, pipe_loop: &PipeLoop) -> String {
+        let regions = self.regions(pipe_loop);
+        let mut out = String::new();
+        for (y, row) in regions.iter().enumerate() {
+            let mut style = "";
+            for (x, region) in row.iter().enumerate() {
+                let pipe = self.type_at((y, x));
+                let (next_style, glyph) = match region {
+                    Region::Loop if Some((y, x)) == self.start => {
+                        (LOOP_START, self.shape_at((y, x)).unwrap().glyph())
+                    }
+                    Region::Loop => (LOOP, pipe.glyph()),
+                    Region::Inside if pipe == PipeType::GROUND => (INSIDE, pipe.glyph()),
+                    Region::Inside => (INSIDE_JUNK, pipe.glyph()),
+                    Region::Outside if pipe == PipeType::GROUND => (OUTSIDE, pipe.glyph()),
+                    Region::Outside => (OUTSIDE_JUNK, pipe.glyph()),
+                };
+                if next_style != style {
+                    if !style.is_empty() {
+                        out += RESET;
+                    }
+                    out += next_style;
+                    style = next_style;
+                }
+                out.push(glyph);
+            }
+            out += RESET;
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn write_render(&self, pipe_loop: &PipeLoop, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.render(pipe_loop))
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+const LOOP: &str = "\x1b[1;33m";
+const LOOP_START: &str = "\x1b[1;31m";
+const INSIDE: &str = "\x1b[42m";
+const INSIDE_JUNK: &str = "\x1b[2;42m";
+const OUTSIDE: &str = "\x1b[100m";
+const OUTSIDE_JUNK: &str = "\x1b[2;100m";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Diagnostic {
+    UnresolvedStart {
+        tile: (usize, usize),
+        open: usize,
+    },
+    OffMap {
+        tile: (usize, usize),
+        dir: Direction,
+    },
+    Dangling {
+        tile: (usize, usize),
+        dir: Direction,
+        target: (usize, usize),
+    },
+}
+
+// coordinates as (row, column), counting from 0
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Diagnostic::UnresolvedStart { tile, open } => {
+                write!(f, "S at {:?} connects {} ways, not 2", tile, open)
+            }
+            Diagnostic::OffMap { tile, dir } => {
+                write!(f, "pipe at {:?} leads {:?} off the map", tile, dir)
+            }
+            Diagnostic::Dangling { tile, dir, target } => write!(
+                f,
+                "pipe at {:?} leads {:?} into {:?}, which doesn't connect back",
+                tile, dir, target
+            ),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Region {
+    Loop,
+    Inside,
+    Outside,
+}
+
+// A closed loop of pipes, in walking order from its first tile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipeLoop {
+    pub tiles: Vec<(usize, usize)>,
+    // the pipe on the first tile, inferred when that's `S`
+    pub start_shape: PipeType,
+}
+
+impl PipeLoop {
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    // the corners of the loop as a polygon, i.e. the tiles where it turns
+    pub fn vertices(&self) -> Vec<(usize, usize)> {
+        let n = self.tiles.len();
+        (0..n)
+            .filter(|&i| {
+                let (py, px) = self.tiles[(i + n - 1) % n];
+                let (ny, nx) = self.tiles[(i + 1) % n];
+                py != ny && px != nx
+            })
+            .map(|i| self.tiles[i])
+            .collect()
+    }
+
+    // shoelace formula over the tile centres
+    pub fn twice_area(&self) -> usize {
+        let vertices = self.vertices();
+        let n = vertices.len();
+        let sum: i64 = (0..n)
+            .map(|i| {
+                let (y1, x1) = vertices[i];
+                let (y2, x2) = vertices[(i + 1) % n];
+                x1 as i64 * y2 as i64 - x2 as i64 * y1 as i64
+            })
+            .sum();
+        sum.unsigned_abs() as usize
+    }
+
+    // Pick's theorem, A = I + B/2 - 1, with every loop tile a boundary point
+    pub fn enclosed(&self) -> usize {
+        (self.twice_area() + 2 - self.len()) / 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagnostic, Direction, PipeMap, PipeType};
+
+    const ENCLOSED: &str = "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+
+    // three loops one inside the next, a loop beside them, and a broken
+    // chain of pipes
+    const NESTED: &str = "F------7.F7
+|F----7|.LJ
+||F7..||...
+||LJ..||.-7
+|L----J|...
+L------J...";
+
+    #[test]
+    fn main_loop_encloses_the_example() {
+        let map = PipeMap::parse(ENCLOSED);
+        assert_eq!(map.start_shape(), Some(PipeType::SE));
+        let main_loop = map.main_loop().unwrap();
+        assert_eq!(main_loop.enclosed(), 4);
+        assert_eq!(map.enclosed_by_flood(&main_loop), 4);
+        assert_eq!(map.loops(), vec![main_loop]);
+        assert_eq!(map.diagnostics(), vec![]);
+    }
+
+    #[test]
+    fn loops_are_found_in_reading_order() {
+        let map = PipeMap::parse(NESTED);
+        let loops = map.loops();
+        let firsts: Vec<_> = loops.iter().map(|l| l.tiles[0]).collect();
+        assert_eq!(firsts, vec![(0, 0), (0, 9), (1, 1), (2, 2)]);
+        let enclosed: Vec<_> = loops.iter().map(|l| l.enclosed()).collect();
+        assert_eq!(enclosed, vec![24, 0, 8, 0]);
+        for pipe_loop in &loops {
+            assert_eq!(pipe_loop.enclosed(), map.enclosed_by_flood(pipe_loop));
+        }
+    }
+
+    #[test]
+    fn nesting_finds_the_innermost_loop_around() {
+        let map = PipeMap::parse(NESTED);
+        assert_eq!(
+            map.nesting(&map.loops()),
+            vec![None, None, Some(0), Some(2)]
+        );
+    }
+
+    #[test]
+    fn nesting_handles_many_side_by_side_loops() {
+        let map = PipeMap::parse(&"F7F7F7F7\nLJLJLJLJ\n".repeat(4));
+        let loops = map.loops();
+        assert_eq!(loops.len(), 16);
+        assert_eq!(map.nesting(&loops), vec![None; 16]);
+    }
+
+    #[test]
+    fn diagnostics_point_at_broken_pipe_ends() {
+        let map = PipeMap::parse(NESTED);
+        assert_eq!(
+            map.diagnostics(),
+            vec![
+                Diagnostic::Dangling {
+                    tile: (3, 9),
+                    dir: Direction::West,
+                    target: (3, 8),
+                },
+                Diagnostic::Dangling {
+                    tile: (3, 10),
+                    dir: Direction::South,
+                    target: (4, 10),
+                },
+            ]
+        );
+        let edge = PipeMap::parse("-S-\n.|.");
+        assert_eq!(
+            edge.diagnostics(),
+            vec![
+                Diagnostic::UnresolvedStart {
+                    tile: (0, 1),
+                    open: 3,
+                },
+                Diagnostic::OffMap {
+                    tile: (0, 0),
+                    dir: Direction::West,
+                },
+                Diagnostic::OffMap {
+                    tile: (0, 2),
+                    dir: Direction::East,
+                },
+                Diagnostic::OffMap {
+                    tile: (1, 1),
+                    dir: Direction::South,
+                },
+            ]
+        );
+    }
+}
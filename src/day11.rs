@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+const GALAXY: char = '#';
+
+#[derive(Debug, Clone)]
+pub struct StarMap {
+    // (row, column) before expansion, in reading order
+    pub galaxies: Vec<(usize, usize)>,
+    // empty_rows[r]: how many galaxy-free rows come before row r
+    empty_rows: Vec<usize>,
+    empty_cols: Vec<usize>,
+}
+
+impl StarMap {
+    pub fn parse(input: &str) -> StarMap {
+        let grid: Vec<Vec<char>> = input
+            .trim()
+            .lines()
+            .map(|line| line.trim().chars().collect())
+            .collect();
+        let height = grid.len();
+        let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+
+        let mut galaxies = Vec::new();
+        let mut row_has = vec![false; height];
+        let mut col_has = vec![false; width];
+        for (y, row) in grid.iter().enumerate() {
+            for (x, c) in row.iter().enumerate() {
+                if *c == GALAXY {
+                    galaxies.push((y, x));
+                    row_has[y] = true;
+                    col_has[x] = true;
+                }
+            }
+        }
+
+        StarMap {
+            galaxies,
+            empty_rows: empty_before(&row_has),
+            empty_cols: empty_before(&col_has),
+        }
+    }
+
+    // Galaxies are named by number as in the puzzle: 1 for the first one in
+    // reading order. Every empty row and column becomes `factor` of them.
+    pub fn position(&self, galaxy: usize, factor: &BigUint) -> (BigUint, BigUint) {
+        let (y, x) = self.galaxies[galaxy - 1];
+        let expand = |c: usize, empty: usize| BigUint::from(c - empty) + factor * empty;
+        (expand(y, self.empty_rows[y]), expand(x, self.empty_cols[x]))
+    }
+
+    fn positions(&self, factor: &BigUint) -> Vec<(BigUint, BigUint)> {
+        (1..=self.galaxies.len())
+            .map(|g| self.position(g, factor))
+            .collect()
+    }
+
+    pub fn distance(&self, a: usize, b: usize, factor: &BigUint) -> BigUint {
+        manhattan(&self.position(a, factor), &self.position(b, factor))
+    }
+
+    // the k galaxies closest to `galaxy`, nearest first, ties by number
+    pub fn nearest(&self, galaxy: usize, k: usize, factor: &BigUint) -> Vec<(usize, BigUint)> {
+        let from = self.position(galaxy, factor);
+        let mut others: Vec<(BigUint, usize)> = self
+            .positions(factor)
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (manhattan(&from, p), i + 1))
+            .filter(|(_, g)| *g != galaxy)
+            .collect();
+        if k < others.len() {
+            others.select_nth_unstable(k);
+            others.truncate(k);
+        }
+        others.sort_unstable();
+        others.into_iter().map(|(d, g)| (g, d)).collect()
+    }
+
+    // how many unordered pairs fall in each bucket of `width` distances,
+    // keyed by the bucket's smallest distance; a width of 1 counts every
+    // distance exactly
+    pub fn histogram(&self, factor: &BigUint, width: &BigUint) -> BTreeMap<BigUint, usize> {
+        assert!(!width.is_zero(), "bucket width must be positive");
+        let positions = self.positions(factor);
+        let mut histogram = BTreeMap::new();
+        for (i, a) in positions.iter().enumerate() {
+            for b in &positions[i + 1..] {
+                let distance = manhattan(a, b);
+                let bucket = if width.is_one() {
+                    distance
+                } else {
+                    &distance - &distance % width
+                };
+                *histogram.entry(bucket).or_default() += 1;
+            }
+        }
+        histogram
+    }
+
+    // Sum of Manhattan distances over all unordered pairs. An expanded
+    // coordinate is c + (factor - 1) * empty_before(c), and both terms only
+    // grow with c, so the pairwise sum splits into one for the raw
+    // coordinates and one for the empty counts, each done in u128 with
+    // prefix sums and only scaled up by the factor at the end.
+    pub fn total_distance(&self, factor: &BigUint) -> BigUint {
+        let mut raw = 0;
+        let mut empty = 0;
+        for axis in [0, 1] {
+            let mut coords: Vec<usize> = self
+                .galaxies
+                .iter()
+                .map(|g| if axis == 0 { g.0 } else { g.1 })
+                .collect();
+            coords.sort_unstable();
+            let counts = if axis == 0 {
+                &self.empty_rows
+            } else {
+                &self.empty_cols
+            };
+            raw += pairwise_sum(coords.iter().copied());
+            empty += pairwise_sum(coords.iter().map(|c| counts[*c]));
+        }
+        // every empty line crossed is already counted once in `raw`
+        BigUint::from(raw - empty) + factor * BigUint::from(empty)
+    }
+}
+
+fn manhattan(a: &(BigUint, BigUint), b: &(BigUint, BigUint)) -> BigUint {
+    let diff = |p: &BigUint, q: &BigUint| if p > q { p - q } else { q - p };
+    diff(&a.0, &b.0) + diff(&a.1, &b.1)
+}
+
+fn empty_before(has_galaxy: &[bool]) -> Vec<usize> {
+    has_galaxy
+        .iter()
+        .scan(0, |empty, has| {
+            let before = *empty;
+            *empty += usize::from(!has);
+            Some(before)
+        })
+        .collect()
+}
+
+// sum of |a - b| over all pairs of an ascending sequence
+fn pairwise_sum(sorted: impl Iterator<Item = usize>) -> u128 {
+    let mut total = 0;
+    let mut prefix = 0;
+    for (i, v) in sorted.enumerate() {
+        let v = v as u128;
+        total += v * i as u128 - prefix;
+        prefix += v;
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use num_bigint::BigUint;
+
+    use super::StarMap;
+    use crate::lcg::Lcg;
+
+    const EXAMPLE: &str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+
+    fn random_map(lcg: &mut Lcg) -> String {
+        let height = 1 + lcg.below(8);
+        let width = 1 + lcg.below(8);
+        (0..height)
+            .map(|_| {
+                (0..width)
+                    .map(|_| if lcg.below(5) == 0 { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn example_totals() {
+        let map = StarMap::parse(EXAMPLE);
+        for (factor, total) in [(2u32, 374u32), (10, 1030), (100, 8410)] {
+            assert_eq!(
+                map.total_distance(&BigUint::from(factor)),
+                BigUint::from(total)
+            );
+        }
+    }
+
+    #[test]
+    fn total_distance_matches_every_pair() {
+        let mut lcg = Lcg::new(0x2023_0011);
+        for _ in 0..300 {
+            let input = random_map(&mut lcg);
+            let map = StarMap::parse(&input);
+            let factor = BigUint::from(1 + lcg.below(1000));
+            let positions: Vec<(BigUint, BigUint)> = (1..=map.galaxies.len())
+                .map(|g| map.position(g, &factor))
+                .collect();
+            let diff = |p: &BigUint, q: &BigUint| if p > q { p - q } else { q - p };
+            let mut brute = BigUint::default();
+            for (i, a) in positions.iter().enumerate() {
+                for b in &positions[i + 1..] {
+                    brute += diff(&a.0, &b.0) + diff(&a.1, &b.1);
+                }
+            }
+            assert_eq!(map.total_distance(&factor), brute, "{}", input);
+        }
+    }
+
+    #[test]
+    fn example_pair_distances() {
+        let map = StarMap::parse(EXAMPLE);
+        let two = BigUint::from(2u32);
+        for (a, b, distance) in [(5, 9, 9u32), (1, 7, 15), (3, 6, 17), (8, 9, 5)] {
+            assert_eq!(map.distance(a, b, &two), BigUint::from(distance));
+            assert_eq!(map.distance(b, a, &two), BigUint::from(distance));
+        }
+        assert_eq!(
+            map.nearest(5, 2, &two),
+            vec![(3, BigUint::from(5u32)), (8, BigUint::from(6u32))]
+        );
+    }
+
+    #[test]
+    fn queries_match_every_pair() {
+        let mut lcg = Lcg::new(0x2023_0046);
+        for _ in 0..300 {
+            let input = random_map(&mut lcg);
+            let map = StarMap::parse(&input);
+            let factor = BigUint::from(1 + lcg.below(1000));
+            let n = map.galaxies.len();
+            let pairs: Vec<(usize, usize, BigUint)> = (1..=n)
+                .flat_map(|a| (a + 1..=n).map(move |b| (a, b)))
+                .map(|(a, b)| (a, b, map.distance(a, b, &factor)))
+                .collect();
+
+            let total: BigUint = pairs.iter().map(|(_, _, d)| d).sum();
+            assert_eq!(map.total_distance(&factor), total, "{}", input);
+
+            let width = BigUint::from(1 + lcg.below(20));
+            let mut histogram = BTreeMap::new();
+            for (_, _, d) in &pairs {
+                *histogram.entry(d - d % &width).or_default() += 1;
+            }
+            assert_eq!(map.histogram(&factor, &width), histogram, "{}", input);
+
+            for galaxy in 1..=n {
+                let mut others: Vec<(BigUint, usize)> = (1..=n)
+                    .filter(|g| *g != galaxy)
+                    .map(|g| (map.distance(galaxy, g, &factor), g))
+                    .collect();
+                others.sort();
+                let k = lcg.below(n + 1);
+                let expected: Vec<(usize, BigUint)> =
+                    others.into_iter().take(k).map(|(d, g)| (g, d)).collect();
+                assert_eq!(map.nearest(galaxy, k, &factor), expected, "{}", input);
+            }
+        }
+    }
+}
@@ -0,0 +1,1086 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::env;
+use std::error::Error;
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+pub const OPERATIONAL: u8 = b'.';
+pub const DAMAGED: u8 = b'#';
+pub const UNKNOWN: u8 = b'?';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpringRow {
+    pub springs: Vec<u8>,
+    pub groups: Vec<usize>,
+}
+
+impl SpringRow {
+    // "???.### 1,1,3"
+    pub fn parse(line: &str) -> SpringRow {
+        let (springs, groups) = line.trim().split_once(' ').unwrap();
+        let springs = springs.as_bytes().to_vec();
+        assert!(
+            springs
+                .iter()
+                .all(|s| matches!(*s, OPERATIONAL | DAMAGED | UNKNOWN)),
+            "oh no! {}",
+            line
+        );
+        let groups = groups
+            .split(',')
+            .filter(|g| !g.is_empty())
+            .map(|g| g.parse().unwrap())
+            .collect();
+        SpringRow { springs, groups }
+    }
+
+    pub fn unfold(&self, unfolding: &Unfolding) -> SpringRow {
+        let copies = unfolding.copies as usize;
+        let mut springs = Vec::new();
+        for i in 0..copies {
+            if i > 0 {
+                springs.extend_from_slice(&unfolding.separator);
+            }
+            springs.extend_from_slice(&self.springs);
+        }
+        SpringRow {
+            springs,
+            groups: self.groups.repeat(copies),
+        }
+    }
+
+    // The same count as unfolding and counting, without building the
+    // unfolded row. Rows whose groups stay near their own copies take any
+    // number of copies (see `Transfer`); rows whose groups can drift between
+    // copies, like `??? 1`, are only counted up to MAX_DRIFTING_COPIES.
+    pub fn unfolded_arrangements(&self, unfolding: &Unfolding) -> Result<BigUint, Drifting> {
+        let copies = unfolding.copies;
+        if self.groups.is_empty() {
+            let damaged = self.springs.contains(&DAMAGED)
+                || (copies > 1 && unfolding.separator.contains(&DAMAGED));
+            return Ok(match copies > 0 && damaged {
+                true => BigUint::zero(),
+                false => BigUint::one(),
+            });
+        }
+        if copies < 2 {
+            return Ok(self.unfold(unfolding).arrangements());
+        }
+        match Transfer::new(self, &unfolding.separator) {
+            Some(transfer) => Ok(transfer.count(copies)),
+            None if copies <= MAX_DRIFTING_COPIES => {
+                Ok(drifting_arrangements(self, &unfolding.separator, copies))
+            }
+            None => Err(Drifting {
+                row: self.to_string(),
+                copies,
+            }),
+        }
+    }
+
+    pub fn arrangements(&self) -> BigUint {
+        let states = States::new(&self.groups);
+        let mut next = states.accepting();
+        let mut ways = vec![BigUint::zero(); states.len()];
+        for &spring in self.springs.iter().rev() {
+            states.step_back(spring, &next, &mut ways);
+            std::mem::swap(&mut next, &mut ways);
+        }
+        next.swap_remove(0)
+    }
+
+    // arrangements in lexicographic order, '#' before '.'
+    pub fn iter_arrangements(&self) -> Arrangements {
+        Arrangements::new(self)
+    }
+
+    // the k-th arrangement in that order, counting from 0
+    pub fn arrangement(&self, k: &BigUint) -> Option<String> {
+        Arrangements::new(self).get(k)
+    }
+
+    // uniformly at random, with `random` supplying uniform u64s
+    pub fn sample(&self, random: impl FnMut() -> u64) -> Option<String> {
+        Arrangements::new(self).sample(random)
+    }
+
+    // The row with every unknown spring that has the same state in all
+    // arrangements filled in, or None if there are no arrangements. A spring
+    // can be damaged if some state reachable just before it has a damaged
+    // transition with completions left after it, and likewise for operational.
+    pub fn settle(&self) -> Option<Vec<u8>> {
+        let table = Arrangements::new(self);
+        if table.total().is_zero() {
+            return None;
+        }
+        let states = &table.states;
+        let mut reachable = vec![false; states.len()];
+        reachable[0] = true;
+        let mut settled = self.springs.clone();
+        for (i, &spring) in self.springs.iter().enumerate() {
+            let after = &table.ways[i + 1];
+            let mut next = vec![false; states.len()];
+            let (mut can_damage, mut can_operate) = (false, false);
+            for s in (0..states.len()).filter(|s| reachable[*s]) {
+                for (allowed, step, can) in [
+                    (spring != OPERATIONAL, &states.on_damaged, &mut can_damage),
+                    (spring != DAMAGED, &states.on_operational, &mut can_operate),
+                ] {
+                    if let (true, Some(t)) = (allowed, step[s]) {
+                        if !after[t].is_zero() {
+                            next[t] = true;
+                            *can = true;
+                        }
+                    }
+                }
+            }
+            settled[i] = match (can_damage, can_operate) {
+                (true, false) => DAMAGED,
+                (false, true) => OPERATIONAL,
+                _ => spring,
+            };
+            reachable = next;
+        }
+        Some(settled)
+    }
+}
+
+// Keeps the completion counts for every position, so any arrangement can be
+// found by index in a single walk down the row: at each spring take '#' if
+// the index falls among the completions that start with it, otherwise skip
+// past those and take '.'.
+#[derive(Debug, Clone)]
+pub struct Arrangements {
+    springs: Vec<u8>,
+    states: States,
+    // ways[i][s]: completions of springs[i..] from state s
+    ways: Vec<Vec<BigUint>>,
+    next: BigUint,
+}
+
+impl Arrangements {
+    fn new(row: &SpringRow) -> Arrangements {
+        let states = States::new(&row.groups);
+        let mut ways = vec![states.accepting()];
+        for &spring in row.springs.iter().rev() {
+            let mut layer = vec![BigUint::zero(); states.len()];
+            states.step_back(spring, ways.last().unwrap(), &mut layer);
+            ways.push(layer);
+        }
+        ways.reverse();
+        Arrangements {
+            springs: row.springs.clone(),
+            states,
+            ways,
+            next: BigUint::zero(),
+        }
+    }
+
+    pub fn total(&self) -> &BigUint {
+        &self.ways[0][0]
+    }
+
+    pub fn get(&self, k: &BigUint) -> Option<String> {
+        if k >= self.total() {
+            return None;
+        }
+        let mut k = k.clone();
+        let mut state = 0;
+        let mut arrangement = String::with_capacity(self.springs.len());
+        for (i, &spring) in self.springs.iter().enumerate() {
+            let damaged = match (spring != OPERATIONAL, self.states.on_damaged[state]) {
+                (true, Some(t)) => Some((t, &self.ways[i + 1][t])),
+                _ => None,
+            };
+            match damaged {
+                Some((t, count)) if k < *count => {
+                    arrangement.push(DAMAGED as char);
+                    state = t;
+                }
+                _ => {
+                    if let Some((_, count)) = damaged {
+                        k -= count;
+                    }
+                    arrangement.push(OPERATIONAL as char);
+                    state = self.states.on_operational[state].unwrap();
+                }
+            }
+        }
+        Some(arrangement)
+    }
+
+    // rejection sampling for an index below the total, so every arrangement
+    // is equally likely
+    pub fn sample(&self, mut random: impl FnMut() -> u64) -> Option<String> {
+        let total = self.total();
+        if total.is_zero() {
+            return None;
+        }
+        let bits = total.bits();
+        let words = bits.div_ceil(64) as usize;
+        loop {
+            let k = (0..words).fold(BigUint::zero(), |k, _| (k << 64) + random())
+                >> (words as u64 * 64 - bits);
+            if k < *total {
+                return self.get(&k);
+            }
+        }
+    }
+}
+
+impl Iterator for Arrangements {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let arrangement = self.get(&self.next)?;
+        self.next += 1u32;
+        Some(arrangement)
+    }
+}
+
+// back in the "???.### 1,1,3" form
+impl fmt::Display for SpringRow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let groups: Vec<String> = self.groups.iter().map(|g| g.to_string()).collect();
+        write!(
+            f,
+            "{} {}",
+            String::from_utf8_lossy(&self.springs),
+            groups.join(",")
+        )
+    }
+}
+
+pub fn parse_rows(input: &str) -> Vec<SpringRow> {
+    input
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty())
+        .map(SpringRow::parse)
+        .collect()
+}
+
+// The DP state after reading some prefix of a row: g groups finished and a
+// run of r damaged springs so far towards group g. States are numbered
+// offset[g] + r with 0 <= r <= groups[g], then one final state for "every
+// group done", so a whole position's worth of counts is one flat slice.
+#[derive(Debug, Clone)]
+struct States {
+    len: usize,
+    // state reached by reading a damaged / operational spring, if allowed
+    on_damaged: Vec<Option<usize>>,
+    on_operational: Vec<Option<usize>>,
+}
+
+impl States {
+    fn new(groups: &[usize]) -> States {
+        let mut offsets = Vec::with_capacity(groups.len() + 1);
+        let mut len = 0;
+        for g in groups {
+            offsets.push(len);
+            len += g + 1;
+        }
+        offsets.push(len);
+        let done = len;
+        len += 1;
+
+        let mut on_damaged = vec![None; len];
+        let mut on_operational = vec![None; len];
+        on_operational[done] = Some(done);
+        for (g, size) in groups.iter().enumerate() {
+            let base = offsets[g];
+            for r in 0..*size {
+                on_damaged[base + r] = Some(base + r + 1);
+            }
+            // between runs, or right after finishing one
+            on_operational[base] = Some(base);
+            on_operational[base + size] = Some(offsets[g + 1]);
+        }
+        States {
+            len,
+            on_damaged,
+            on_operational,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    // one way to finish from every state that an operational spring past the
+    // end would take to "every group done"
+    fn accepting(&self) -> Vec<BigUint> {
+        let done = self.len - 1;
+        self.on_operational
+            .iter()
+            .map(|next| match next {
+                Some(s) if *s == done => BigUint::one(),
+                _ => BigUint::zero(),
+            })
+            .collect()
+    }
+
+    // ways[s]: completions from state s in front of `spring`, given the
+    // completions `next` after it; reuses the buffers already in `ways`
+    fn step_back(&self, spring: u8, next: &[BigUint], ways: &mut [BigUint]) {
+        let damaged = spring != OPERATIONAL;
+        let operational = spring != DAMAGED;
+        for (s, w) in ways.iter_mut().enumerate() {
+            w.set_zero();
+            if let (true, Some(t)) = (damaged, self.on_damaged[s]) {
+                *w += &next[t];
+            }
+            if let (true, Some(t)) = (operational, self.on_operational[s]) {
+                *w += &next[t];
+            }
+        }
+    }
+}
+
+// A nonogram is a grid whose rows and columns are all spring rows, so every
+// line can be solved with the same counting as above.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nonogram {
+    pub rows: Vec<Vec<usize>>,
+    pub cols: Vec<Vec<usize>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    pub cells: Vec<Vec<u8>>,
+}
+
+impl Grid {
+    fn column(&self, x: usize) -> Vec<u8> {
+        self.cells.iter().map(|row| row[x]).collect()
+    }
+
+    fn is_solved(&self) -> bool {
+        self.cells.iter().flatten().all(|c| *c != UNKNOWN)
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in &self.cells {
+            writeln!(f, "{}", String::from_utf8_lossy(row))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Solution {
+    None,
+    Unique(Grid),
+    // two different solutions, where there may be more
+    Multiple(Grid, Grid),
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Solution::None => writeln!(f, "no solution"),
+            Solution::Unique(grid) => write!(f, "unique solution:\n{}", grid),
+            Solution::Multiple(a, b) => {
+                write!(f, "multiple solutions, e.g.\n{}\nand\n{}", a, b)
+            }
+        }
+    }
+}
+
+impl Nonogram {
+    // "rows" then one clue per line, then "columns" and theirs; a clue is
+    // group sizes split by commas or spaces, with "0" or "-" for a blank line
+    pub fn parse(input: &str) -> Nonogram {
+        let mut rows = Vec::new();
+        let mut cols = Vec::new();
+        let mut section = None;
+        for line in input.lines().map(|line| line.trim()) {
+            match line {
+                "" => continue,
+                "rows" => section = Some(&mut rows),
+                "columns" => section = Some(&mut cols),
+                clue => {
+                    let groups = clue
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .filter(|g| !g.is_empty() && *g != "-")
+                        .map(|g| g.parse().unwrap())
+                        .filter(|g| *g != 0)
+                        .collect();
+                    section
+                        .as_mut()
+                        .expect("clue before a section")
+                        .push(groups);
+                }
+            }
+        }
+        Nonogram { rows, cols }
+    }
+
+    pub fn solve(&self) -> Solution {
+        let blank = Grid {
+            cells: vec![vec![UNKNOWN; self.cols.len()]; self.rows.len()],
+        };
+        let mut found = Vec::new();
+        self.search(blank, &mut found);
+        match found.len() {
+            0 => Solution::None,
+            1 => Solution::Unique(found.pop().unwrap()),
+            _ => {
+                let b = found.pop().unwrap();
+                Solution::Multiple(found.pop().unwrap(), b)
+            }
+        }
+    }
+
+    // settles every row and column until nothing changes; false on a line
+    // with no arrangements left
+    fn propagate(&self, grid: &mut Grid) -> bool {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (y, groups) in self.rows.iter().enumerate() {
+                let line = SpringRow {
+                    springs: grid.cells[y].clone(),
+                    groups: groups.clone(),
+                };
+                let Some(settled) = line.settle() else {
+                    return false;
+                };
+                changed |= settled != grid.cells[y];
+                grid.cells[y] = settled;
+            }
+            for (x, groups) in self.cols.iter().enumerate() {
+                let line = SpringRow {
+                    springs: grid.column(x),
+                    groups: groups.clone(),
+                };
+                let Some(settled) = line.settle() else {
+                    return false;
+                };
+                for (y, cell) in settled.into_iter().enumerate() {
+                    changed |= grid.cells[y][x] != cell;
+                    grid.cells[y][x] = cell;
+                }
+            }
+        }
+        true
+    }
+
+    // Propagates, then guesses on the unfinished row with the fewest
+    // arrangements left, stopping once two solutions are known.
+    fn search(&self, mut grid: Grid, found: &mut Vec<Grid>) {
+        if found.len() >= 2 || !self.propagate(&mut grid) {
+            return;
+        }
+        if grid.is_solved() {
+            found.push(grid);
+            return;
+        }
+        let y = (0..self.rows.len())
+            .filter(|y| grid.cells[*y].contains(&UNKNOWN))
+            .min_by_key(|y| {
+                SpringRow {
+                    springs: grid.cells[*y].clone(),
+                    groups: self.rows[*y].clone(),
+                }
+                .arrangements()
+            })
+            .unwrap();
+        let x = grid.cells[y].iter().position(|c| *c == UNKNOWN).unwrap();
+        for guess in [DAMAGED, OPERATIONAL] {
+            let mut next = grid.clone();
+            next.cells[y][x] = guess;
+            self.search(next, found);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unfolding {
+    pub copies: u64,
+    pub separator: Vec<u8>,
+}
+
+// A row whose groups can drift between copies is counted copy by copy over a
+// window of states and numbers that both grow with the copies, so roughly
+// cubic in them: at this many, a second or so for a typical row and most of a
+// minute for one that is all unknowns. Past it the row is refused rather than
+// left to run for hours.
+pub const MAX_DRIFTING_COPIES: u64 = 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drifting {
+    pub row: String,
+    pub copies: u64,
+}
+
+impl fmt::Display for Drifting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "groups in `{}` can drift between copies, so it can't be unfolded {} times (at most {})",
+            self.row, self.copies, MAX_DRIFTING_COPIES
+        )
+    }
+}
+
+impl Error for Drifting {}
+
+// the puzzle's: five copies joined by an unknown spring
+impl Default for Unfolding {
+    fn default() -> Unfolding {
+        Unfolding {
+            copies: 5,
+            separator: vec![UNKNOWN],
+        }
+    }
+}
+
+impl Unfolding {
+    // DAY12_COPIES and DAY12_SEPARATOR override the defaults on the command
+    // line, e.g. `DAY12_COPIES=1000 DAY12_SEPARATOR=. cargo run --release`
+    pub fn from_env() -> Unfolding {
+        let mut unfolding = Unfolding::default();
+        if let Ok(copies) = env::var("DAY12_COPIES") {
+            unfolding.copies = copies
+                .trim()
+                .parse()
+                .expect("DAY12_COPIES should be a number of copies");
+        }
+        if let Ok(separator) = env::var("DAY12_SEPARATOR") {
+            let separator = separator.into_bytes();
+            assert!(
+                separator
+                    .iter()
+                    .all(|s| matches!(*s, OPERATIONAL | DAMAGED | UNKNOWN)),
+                "DAY12_SEPARATOR should only hold springs"
+            );
+            unfolding.separator = separator;
+        }
+        unfolding
+    }
+}
+
+// Counts the unfolded row one copy at a time. Between copies the DP state is
+// (lag, run): lag is the groups finished so far minus n for every copy read,
+// and run the damaged springs towards the next group. Reading the separator
+// and another copy moves between these states the same way whatever the copy
+// number, so the count for k copies is start * step^(k - 1) * accept.
+//
+// That only works with finitely many states, so first the lag is checked to
+// be bounded: with lags taken mod n the states form a finite graph, and the
+// lag stays bounded exactly when no cycle through states that can still lead
+// to a full arrangement changes it.
+struct Transfer {
+    start: Vec<BigUint>,
+    step: Vec<Vec<BigUint>>,
+    accept: Vec<bool>,
+}
+
+type Lifted = (i64, usize);
+
+impl Transfer {
+    fn new(row: &SpringRow, separator: &[u8]) -> Option<Transfer> {
+        let groups = &row.groups;
+        let n = groups.len();
+        let block: Vec<u8> = separator.iter().chain(&row.springs).copied().collect();
+
+        // states mod n: (group, run) numbered like `States`
+        let mut offsets = Vec::with_capacity(n);
+        let mut nodes = Vec::new();
+        for (j, size) in groups.iter().enumerate() {
+            offsets.push(nodes.len());
+            nodes.extend((0..=*size).map(|r| (j, r)));
+        }
+        let node_of = |(e, r): Lifted| offsets[e.rem_euclid(n as i64) as usize] + r;
+
+        // edges[u]: (v, lag change, ways) over one separator and copy
+        let edges: Vec<Vec<(usize, i64, BigUint)>> = nodes
+            .iter()
+            .map(|&(j, r)| {
+                read_block(groups, j, r, &block)
+                    .into_iter()
+                    .map(|((done, run), ways)| {
+                        let d = done as i64 - n as i64;
+                        (node_of((j as i64 + done as i64, run)), d, ways)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let first: BTreeMap<Lifted, BigUint> = read_block(groups, 0, 0, &row.springs)
+            .into_iter()
+            .map(|((done, run), ways)| ((done as i64 - n as i64, run), ways))
+            .collect();
+        let accepting = [(0, 0), (-1, groups[n - 1])];
+
+        let forward = reach(nodes.len(), first.keys().map(|s| node_of(*s)), |u| {
+            edges[u].iter().map(|e| e.0).collect()
+        });
+        let mut reverse = vec![Vec::new(); nodes.len()];
+        for (u, out) in edges.iter().enumerate() {
+            for (v, _, _) in out {
+                reverse[*v].push(u);
+            }
+        }
+        let backward = reach(nodes.len(), accepting.iter().map(|s| node_of(*s)), |u| {
+            reverse[u].clone()
+        });
+        let relevant: Vec<bool> = (0..nodes.len())
+            .map(|u| forward[u] && backward[u])
+            .collect();
+
+        // give every relevant state a potential so that each edge inside a
+        // strongly connected component changes the lag by the difference; a
+        // clash means a cycle that drifts
+        let reach_from: Vec<Vec<bool>> = (0..nodes.len())
+            .map(|u| reach(nodes.len(), [u], |u| edges[u].iter().map(|e| e.0).collect()))
+            .collect();
+        let same_component = |u: usize, v: usize| reach_from[u][v] && reach_from[v][u];
+        let mut potential: Vec<Option<i64>> = vec![None; nodes.len()];
+        for root in (0..nodes.len()).filter(|u| relevant[*u]) {
+            if potential[root].is_some() {
+                continue;
+            }
+            potential[root] = Some(0);
+            let mut stack = vec![root];
+            while let Some(u) = stack.pop() {
+                let p = potential[u].unwrap();
+                for (v, d, _) in &edges[u] {
+                    if !relevant[*v] || !same_component(u, *v) {
+                        continue;
+                    }
+                    match potential[*v] {
+                        None => {
+                            potential[*v] = Some(p + d);
+                            stack.push(*v);
+                        }
+                        Some(q) if q != p + d => return None,
+                        Some(_) => {}
+                    }
+                }
+            }
+        }
+
+        // now the lifted states worth tracking are finitely many
+        let successors = |(e, r): Lifted| -> Vec<(Lifted, &BigUint)> {
+            edges[node_of((e, r))]
+                .iter()
+                .filter(|(v, _, _)| relevant[*v])
+                .map(|(v, d, ways)| ((e + d, nodes[*v].1), ways))
+                .collect()
+        };
+        let mut seen: BTreeMap<Lifted, Vec<(Lifted, &BigUint)>> = BTreeMap::new();
+        let mut queue: VecDeque<Lifted> = first
+            .keys()
+            .filter(|s| relevant[node_of(**s)])
+            .copied()
+            .collect();
+        while let Some(state) = queue.pop_front() {
+            if seen.contains_key(&state) {
+                continue;
+            }
+            let next = successors(state);
+            queue.extend(next.iter().map(|(t, _)| *t));
+            seen.insert(state, next);
+        }
+
+        // and of those, the ones that can still end up accepted
+        let mut alive: BTreeMap<Lifted, bool> =
+            seen.keys().map(|s| (*s, accepting.contains(s))).collect();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (state, next) in &seen {
+                if !alive[state] && next.iter().any(|(t, _)| alive.get(t) == Some(&true)) {
+                    alive.insert(*state, true);
+                    changed = true;
+                }
+            }
+        }
+        let states: Vec<Lifted> = alive.into_iter().filter(|s| s.1).map(|s| s.0).collect();
+        let index: BTreeMap<Lifted, usize> =
+            states.iter().enumerate().map(|(i, s)| (*s, i)).collect();
+
+        let mut step = vec![vec![BigUint::zero(); states.len()]; states.len()];
+        for (i, state) in states.iter().enumerate() {
+            for (t, ways) in &seen[state] {
+                if let Some(&k) = index.get(t) {
+                    step[i][k] += *ways;
+                }
+            }
+        }
+        let start = states
+            .iter()
+            .map(|s| first.get(s).cloned().unwrap_or_default())
+            .collect();
+        let accept = states.iter().map(|s| accepting.contains(s)).collect();
+        Some(Transfer {
+            start,
+            step,
+            accept,
+        })
+    }
+
+    fn count(&self, copies: u64) -> BigUint {
+        let mut counts = self.start.clone();
+        let mut power = self.step.clone();
+        let mut remaining = copies - 1;
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                counts = times_matrix(&counts, &power);
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                power = matrix_product(&power, &power);
+            }
+        }
+        counts
+            .into_iter()
+            .zip(&self.accept)
+            .filter(|(_, accept)| **accept)
+            .map(|(count, _)| count)
+            .sum()
+    }
+}
+
+// Counts a row whose groups can drift one copy at a time. Between copies the
+// state is (groups finished, run), but only the groups finished that leave
+// the rest of the copies able to finish exactly the others are kept: every
+// separator and copy finishes between `fewest` and `most` groups, so after k
+// copies that is a window at most min(k, copies - k) * (most - fewest) wide.
+fn drifting_arrangements(row: &SpringRow, separator: &[u8], copies: u64) -> BigUint {
+    let groups = &row.groups;
+    let n = groups.len();
+    let copies = copies as usize;
+    let total = copies * n;
+    let block: Vec<u8> = separator.iter().chain(&row.springs).copied().collect();
+    let runs = groups.iter().max().unwrap() + 1;
+
+    // edges[j][r]: (groups finished, run after, ways) over one separator and
+    // copy, starting at group j mod n with a run of r under way
+    let edges: Vec<Vec<Vec<(usize, usize, BigUint)>>> = groups
+        .iter()
+        .enumerate()
+        .map(|(j, size)| {
+            (0..=*size)
+                .map(|r| {
+                    read_block(groups, j, r, &block)
+                        .into_iter()
+                        .map(|((done, run), ways)| (done, run, ways))
+                        .collect()
+                })
+                .collect()
+        })
+        .collect();
+    let finished = || edges.iter().flatten().flatten().map(|e| e.0);
+    let (Some(fewest), Some(most)) = (finished().min(), finished().max()) else {
+        return BigUint::zero();
+    };
+    let window = |k: usize| {
+        let left = copies - k;
+        let lo = (total - 1).saturating_sub(left * most);
+        (lo, total.saturating_sub(left * fewest).max(lo))
+    };
+
+    let (mut lo, hi) = window(1);
+    let mut layer = vec![vec![BigUint::zero(); runs]; hi - lo + 1];
+    for ((done, run), ways) in read_block(groups, 0, 0, &row.springs) {
+        if (lo..=hi).contains(&done) {
+            layer[done - lo][run] = ways;
+        }
+    }
+    for k in 2..=copies {
+        let (next_lo, next_hi) = window(k);
+        let mut next = vec![vec![BigUint::zero(); runs]; next_hi - next_lo + 1];
+        for (i, counts) in layer.iter().enumerate() {
+            let g = lo + i;
+            for (r, count) in counts.iter().enumerate().filter(|(_, c)| !c.is_zero()) {
+                for (done, run, ways) in &edges[g % n][r] {
+                    if (next_lo..=next_hi).contains(&(g + done)) {
+                        let slot = &mut next[g + done - next_lo][*run];
+                        match ways.is_one() {
+                            true => *slot += count,
+                            false => *slot += count * ways,
+                        }
+                    }
+                }
+            }
+        }
+        layer = next;
+        lo = next_lo;
+    }
+    &layer[total - lo][0] + &layer[total - 1 - lo][groups[n - 1]]
+}
+
+// Forward counts over `springs` starting at group `first` (mod the number of
+// groups) with a run of `run` under way: (groups finished, run at the end)
+// mapped to the number of ways there.
+fn read_block(
+    groups: &[usize],
+    first: usize,
+    run: usize,
+    springs: &[u8],
+) -> BTreeMap<(usize, usize), BigUint> {
+    let n = groups.len();
+    let mut counts = BTreeMap::from([((0, run), BigUint::one())]);
+    for &spring in springs {
+        let mut next: BTreeMap<(usize, usize), BigUint> = BTreeMap::new();
+        for ((done, r), ways) in counts {
+            let size = groups[(first + done) % n];
+            if spring != OPERATIONAL && r < size {
+                *next.entry((done, r + 1)).or_default() += &ways;
+            }
+            if spring != DAMAGED && r == 0 {
+                *next.entry((done, 0)).or_default() += &ways;
+            } else if spring != DAMAGED && r == size {
+                *next.entry((done + 1, 0)).or_default() += &ways;
+            }
+        }
+        counts = next;
+    }
+    counts
+}
+
+fn reach(
+    len: usize,
+    from: impl IntoIterator<Item = usize>,
+    next: impl Fn(usize) -> Vec<usize>,
+) -> Vec<bool> {
+    let mut seen = vec![false; len];
+    let mut stack: Vec<usize> = from.into_iter().collect();
+    while let Some(u) = stack.pop() {
+        if !std::mem::replace(&mut seen[u], true) {
+            stack.extend(next(u));
+        }
+    }
+    seen
+}
+
+fn times_matrix(v: &[BigUint], m: &[Vec<BigUint>]) -> Vec<BigUint> {
+    let mut out = vec![BigUint::zero(); v.len()];
+    for (i, x) in v.iter().enumerate().filter(|(_, x)| !x.is_zero()) {
+        for (j, y) in m[i].iter().enumerate().filter(|(_, y)| !y.is_zero()) {
+            out[j] += x * y;
+        }
+    }
+    out
+}
+
+fn matrix_product(a: &[Vec<BigUint>], b: &[Vec<BigUint>]) -> Vec<Vec<BigUint>> {
+    a.iter().map(|row| times_matrix(row, b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+
+    use super::{
+        parse_rows, Grid, Nonogram, Solution, SpringRow, Unfolding, DAMAGED, MAX_DRIFTING_COPIES,
+        OPERATIONAL, UNKNOWN,
+    };
+    use crate::lcg::Lcg;
+
+    const EXAMPLE: &str = "???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1";
+
+    const SEPARATORS: [&str; 5] = ["?", ".", "#", "?#", ""];
+
+    fn random_rows(lcg: &mut Lcg, count: usize) -> Vec<SpringRow> {
+        (0..count)
+            .map(|_| {
+                let springs: Vec<u8> = (0..1 + lcg.below(8))
+                    .map(|_| [OPERATIONAL, DAMAGED, UNKNOWN][lcg.below(3)])
+                    .collect();
+                let groups = (0..lcg.below(4)).map(|_| 1 + lcg.below(3)).collect();
+                SpringRow { springs, groups }
+            })
+            .collect()
+    }
+
+    // every way to fill in the unknowns, kept if the groups come out right
+    fn brute_arrangements(row: &SpringRow) -> Vec<String> {
+        let unknowns = row.springs.iter().filter(|s| **s == UNKNOWN).count();
+        let mut found: Vec<String> = (0..1u32 << unknowns)
+            .map(|mut bits| {
+                row.springs
+                    .iter()
+                    .map(|&s| match s {
+                        UNKNOWN => {
+                            let damaged = bits & 1 == 0;
+                            bits >>= 1;
+                            if damaged {
+                                DAMAGED as char
+                            } else {
+                                OPERATIONAL as char
+                            }
+                        }
+                        s => s as char,
+                    })
+                    .collect::<String>()
+            })
+            .filter(|filled| {
+                let groups: Vec<usize> = filled
+                    .split(OPERATIONAL as char)
+                    .filter(|g| !g.is_empty())
+                    .map(|g| g.len())
+                    .collect();
+                groups == row.groups
+            })
+            .collect();
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn arrangements_are_listed_indexed_and_sampled_in_order() {
+        let mut rows = parse_rows(EXAMPLE);
+        let mut lcg = Lcg::new(0x2023_0048);
+        rows.extend(random_rows(&mut lcg, 300));
+        for row in &rows {
+            let expected = brute_arrangements(row);
+            let total = row.arrangements();
+            assert_eq!(total, BigUint::from(expected.len()), "{}", row);
+            let listed: Vec<String> = row.iter_arrangements().collect();
+            assert_eq!(listed, expected, "{}", row);
+            for (k, arrangement) in expected.iter().enumerate() {
+                assert_eq!(
+                    row.arrangement(&BigUint::from(k)).as_ref(),
+                    Some(arrangement)
+                );
+            }
+            assert_eq!(row.arrangement(&total), None);
+
+            // only the top bits of the lcg are any good
+            let mut random = || lcg.next_u64() << 33 | lcg.next_u64();
+            let mut seen = vec![false; expected.len()];
+            for _ in 0..200 {
+                match row.sample(&mut random) {
+                    Some(sample) => seen[expected.binary_search(&sample).unwrap()] = true,
+                    None => assert!(expected.is_empty(), "{}", row),
+                }
+            }
+            if expected.len() <= 8 {
+                assert!(seen.iter().all(|s| *s), "{}", row);
+            }
+        }
+    }
+
+    #[test]
+    fn example_unfolds_to_525152() {
+        let unfolding = Unfolding::default();
+        let total: BigUint = parse_rows(EXAMPLE)
+            .iter()
+            .map(|row| row.unfolded_arrangements(&unfolding).unwrap())
+            .sum();
+        assert_eq!(total, BigUint::from(525152u32));
+    }
+
+    #[test]
+    fn unfolded_arrangements_match_literal_unfolding() {
+        let mut rows = parse_rows(EXAMPLE);
+        rows.extend(random_rows(&mut Lcg::new(12), 300));
+        for separator in SEPARATORS {
+            for copies in 0..7 {
+                let unfolding = Unfolding {
+                    copies,
+                    separator: separator.as_bytes().to_vec(),
+                };
+                for row in &rows {
+                    assert_eq!(
+                        row.unfolded_arrangements(&unfolding).unwrap(),
+                        row.unfold(&unfolding).arrangements(),
+                        "`{}` unfolded {} times with `{}`",
+                        row,
+                        copies,
+                        separator
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn drifting_rows_count_many_copies() {
+        // `???` copies joined by `?` are 4c - 1 unknowns holding c single
+        // springs, so C(3c, c) ways
+        let copies = 300;
+        let unfolding = Unfolding {
+            copies,
+            separator: vec![UNKNOWN],
+        };
+        let binomial = (1..=copies).fold(BigUint::from(1u32), |b, i| b * (2 * copies + i) / i);
+        let row = SpringRow::parse("??? 1");
+        assert_eq!(row.unfolded_arrangements(&unfolding), Ok(binomial));
+
+        let past = Unfolding {
+            copies: MAX_DRIFTING_COPIES + 1,
+            ..unfolding
+        };
+        let error = row.unfolded_arrangements(&past).unwrap_err();
+        assert_eq!(error.row, "??? 1");
+        assert_eq!(error.copies, MAX_DRIFTING_COPIES + 1);
+    }
+
+    #[test]
+    fn settled_springs_agree_in_every_arrangement() {
+        let mut rows = parse_rows(EXAMPLE);
+        rows.extend(random_rows(&mut Lcg::new(0x2023_0049), 300));
+        for row in &rows {
+            let arrangements = brute_arrangements(row);
+            let expected = (!arrangements.is_empty()).then(|| {
+                (0..row.springs.len())
+                    .map(|i| {
+                        let first = arrangements[0].as_bytes()[i];
+                        if arrangements.iter().all(|a| a.as_bytes()[i] == first) {
+                            first
+                        } else {
+                            UNKNOWN
+                        }
+                    })
+                    .collect::<Vec<u8>>()
+            });
+            assert_eq!(row.settle(), expected, "{}", row);
+        }
+    }
+
+    fn grid(rows: &[&str]) -> Grid {
+        Grid {
+            cells: rows.iter().map(|row| row.as_bytes().to_vec()).collect(),
+        }
+    }
+
+    #[test]
+    fn nonograms_solve_uniquely_ambiguously_or_not_at_all() {
+        let plus = Nonogram::parse("rows\n1\n3\n1\ncolumns\n1\n3\n1\n");
+        assert_eq!(plus.solve(), Solution::Unique(grid(&[".#.", "###", ".#."])));
+
+        // an A
+        let letter = Nonogram::parse("rows\n2\n1 1\n4\n1 1\n1 1\ncolumns\n4\n1,1\n1,1\n4\n");
+        assert_eq!(
+            letter.solve(),
+            Solution::Unique(grid(&[".##.", "#..#", "####", "#..#", "#..#"]))
+        );
+
+        let either = Nonogram::parse("rows\n1\n1\ncolumns\n1\n1\n");
+        assert_eq!(
+            either.solve(),
+            Solution::Multiple(grid(&["#.", ".#"]), grid(&[".#", "#."]))
+        );
+
+        let blank = Nonogram::parse("rows\n-\n0\ncolumns\n\n0\n-\n");
+        assert_eq!(blank.rows, vec![Vec::<usize>::new(); 2]);
+        assert_eq!(blank.solve(), Solution::Unique(grid(&["..", ".."])));
+
+        let contradiction = Nonogram::parse("rows\n2\n0\ncolumns\n1\n0\n");
+        assert_eq!(contradiction.solve(), Solution::None);
+    }
+}
@@ -0,0 +1,26 @@
+// A small linear congruential generator for reproducible test data and
+// generated inputs. The low bits of the state are poor, so only the top 31
+// are handed out; nothing here needs better randomness than that.
+#[derive(Debug, Clone)]
+pub struct Lcg {
+    state: u64,
+}
+
+impl Lcg {
+    pub fn new(seed: u64) -> Lcg {
+        Lcg { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.state >> 33
+    }
+
+    // close enough to uniform in 0..n for the small n tests use
+    pub fn below(&mut self, n: usize) -> usize {
+        self.next_u64() as usize % n
+    }
+}
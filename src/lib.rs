@@ -3,28 +3,40 @@ extern crate aoc_runner;
 #[macro_use]
 extern crate aoc_runner_derive;
 
+pub mod lcg;
+
 mod day01part1;
 mod day01part2;
 mod day02part1;
 mod day02part2;
+pub mod day03;
 mod day03part1;
 mod day03part2;
+pub mod day04;
 mod day04part1;
 mod day04part2;
+pub mod day05;
 mod day05part1;
 mod day05part2;
+pub mod day06;
 mod day06part1;
 mod day06part2;
+pub mod day07;
 mod day07part1;
 mod day07part2;
+pub mod day08;
 mod day08part1;
 mod day08part2;
+pub mod day09;
 mod day09part1;
 mod day09part2;
+pub mod day10;
 mod day10part1;
 mod day10part2;
+pub mod day11;
 mod day11part1;
 mod day11part2;
+pub mod day12;
 mod day12part1;
 mod day12part2;
 mod day13part1;